@@ -1,17 +1,24 @@
-use klatt::{FrameParms, GlottalSourceType, MainParms};
+use klatt::{FrameParms, FrameSwitchPolicy, GlottalSourceType, MainParms};
 
 pub fn m_parms() -> MainParms {
     MainParms {
         sample_rate: 44100,
         glottal_source_type: GlottalSourceType::Impulsive,
+        frame_switch_policy: FrameSwitchPolicy::PeriodSynchronous,
     }
 }
 
 pub fn f_params() -> FrameParms {
     FrameParms {
-        duration: 1,
+        duration: 1.0,
         f0: 247.0,
+        f0_contour: None,
         flutter_level: 0.25,
+        vibrato_rate: 0.0,
+        vibrato_depth: 0.0,
+        tremolo_rate: 0.0,
+        tremolo_depth: 0.0,
+        drift_level: 0.0,
         open_phase_ratio: 0.7,
         breathiness_db: -25.0,
         tilt_db: 0.0,
@@ -39,4 +46,6 @@ pub fn f_params() -> FrameParms {
     }
 }
 
+// only needed so this file also compiles as its own example
+#[allow(dead_code)]
 fn main() {}
@@ -1,8 +1,10 @@
+// `StepRng` is deprecated without replacement, but it is what the reference output was generated with.
+#![allow(deprecated)]
 use hound::{SampleFormat, WavSpec, WavWriter};
 use klatt::{generate_sound, get_vocal_tract_transfer_function_coefficients};
 mod _params;
 use _params::{f_params, m_parms};
-use rand::{rngs::mock::StepRng, SeedableRng};
+use rand::rngs::mock::StepRng;
 
 fn run_generate_sound() {
     // used for deterministic, portable output
@@ -0,0 +1,246 @@
+//! LPC analysis of recorded speech, to estimate frame parameters for copy synthesis.
+
+use crate::klatt::{get_sample_position, FrameParms, MAX_ORAL_FORMANTS};
+use crate::math::{log10, sqrt};
+use crate::pitch::{estimate_pitch, PitchParms};
+use crate::poles_zeros::get_resonances;
+use crate::spectrum::Window;
+use alloc::{vec, vec::Vec};
+
+/// Parameters for the analysis.
+#[derive(Clone, Debug)]
+pub struct AnalysisParms {
+    /// number of frames per second
+    pub frame_rate: f64,
+    /// length of the LPC analysis window in seconds
+    pub window_duration: f64,
+    /// LPC order, 0 = automatic (2 + sample rate in kHz)
+    pub lpc_order: usize,
+    /// pre-emphasis coefficient, 0 = no pre-emphasis, typically 0.97
+    pub pre_emphasis: f64,
+    /// minimum formant frequency in Hz
+    pub min_formant_freq: f64,
+    /// maximum formant frequency in Hz
+    pub max_formant_freq: f64,
+    /// maximum formant bandwidth in Hz, wider resonances are not treated as formants
+    pub max_formant_bw: f64,
+    /// parameters for the estimation of f0 and voicing
+    pub pitch_parms: PitchParms,
+    /// frames with an RMS level below this level in dB (relative to full scale 1.0) are silent
+    pub silence_db: f64,
+}
+
+impl Default for AnalysisParms {
+    fn default() -> Self {
+        Self {
+            frame_rate: 100.0,
+            window_duration: 0.025,
+            lpc_order: 0,
+            pre_emphasis: 0.97,
+            min_formant_freq: 90.0,
+            max_formant_freq: 5500.0,
+            max_formant_bw: 600.0,
+            pitch_parms: PitchParms::default(),
+            silence_db: -60.0,
+        }
+    }
+}
+
+/// Estimates a sequence of frame parameters from a recorded signal.
+///
+/// Each frame is analyzed with a Hamming-windowed LPC analysis (autocorrelation method) around
+/// its center. The formants are the roots of the LPC polynomial within the formant frequency
+/// range and below the maximum bandwidth, the lowest ones are used as F1 .. F6.
+/// Formants that are not found are set to NaN, i.e. their resonators are bypassed.
+///
+/// The fundamental frequency and voicing are estimated with `estimate_pitch`.
+/// Voiced frames are synthesized with voicing in the cascade branch, unvoiced frames with
+/// aspiration in the cascade branch. The output level of each frame is set by the automatic gain
+/// control to the RMS level of the frame. The result can be passed to `generate_sound`.
+///
+/// ### params
+///
+/// ```text
+/// samples:     The signal.
+/// sample_rate: Sample rate of the signal in Hz.
+/// a_parms:     The analysis parameters.
+/// ```
+///
+/// # Errors
+///
+/// Any invalid parameters will return a static str explaining the invalid param.
+/// Also returns an error if the root finding fails.
+pub fn analyze(
+    samples: &[f64],
+    sample_rate: usize,
+    a_parms: &AnalysisParms,
+) -> Result<Vec<FrameParms>, &'static str> {
+    if sample_rate == 0 {
+        return Err("Invalid sample rate.");
+    }
+    if a_parms.frame_rate.is_nan() || a_parms.frame_rate <= 0.0 {
+        return Err("Invalid frame rate.");
+    }
+    let fs = sample_rate as f64;
+    let order = if a_parms.lpc_order == 0 {
+        2 + sample_rate / 1000
+    } else {
+        a_parms.lpc_order
+    };
+    let window_length = get_sample_position(fs, a_parms.window_duration);
+    if window_length <= order {
+        return Err("LPC window is too short for the LPC order.");
+    }
+    let window = Window::Hamming.coefficients(window_length);
+    let pre_emphasized = get_pre_emphasized(samples, a_parms.pre_emphasis);
+    let frame_duration = 1.0 / a_parms.frame_rate;
+    let frame_count = get_sample_position(a_parms.frame_rate, samples.len() as f64 / fs);
+
+    let mut frames = Vec::with_capacity(frame_count);
+    for i in 0..frame_count {
+        let start = get_sample_position(fs, i as f64 * frame_duration).min(samples.len());
+        let end = get_sample_position(fs, (i + 1) as f64 * frame_duration).min(samples.len());
+        // `usize::midpoint` needs Rust 1.85, and the sum of two sample positions cannot overflow
+        #[allow(clippy::manual_midpoint)]
+        let center = (start + end) / 2;
+        let rms = get_rms(&samples[start..end]);
+        let is_silent = rms == 0.0 || 20.0 * log10(rms) < a_parms.silence_db;
+
+        let segment = get_segment(&pre_emphasized, center, &window);
+        let (formants, f0) = if is_silent {
+            (Vec::new(), 0.0)
+        } else {
+            let a = get_lpc_coefficients(&segment, order)?;
+            let formants = get_resonances(&a, sample_rate)?
+                .into_iter()
+                .filter(|r| {
+                    r.frequency >= a_parms.min_formant_freq
+                        && r.frequency <= a_parms.max_formant_freq
+                        && r.bandwidth > 0.0
+                        && r.bandwidth <= a_parms.max_formant_bw
+                })
+                .take(MAX_ORAL_FORMANTS)
+                .collect();
+            let pitch = estimate_pitch(samples, sample_rate, center, &a_parms.pitch_parms)?;
+            (formants, pitch.f0)
+        };
+
+        let mut oral_formant_freq = vec![f64::NAN; MAX_ORAL_FORMANTS];
+        let mut oral_formant_bw = vec![f64::NAN; MAX_ORAL_FORMANTS];
+        for (j, formant) in formants.iter().enumerate() {
+            oral_formant_freq[j] = formant.frequency;
+            oral_formant_bw[j] = formant.bandwidth;
+        }
+        let is_voiced = f0 > 0.0;
+        frames.push(FrameParms {
+            duration: (end - start) as f64 / fs,
+            f0,
+            f0_contour: None,
+            flutter_level: 0.0,
+            vibrato_rate: 0.0,
+            vibrato_depth: 0.0,
+            tremolo_rate: 0.0,
+            tremolo_depth: 0.0,
+            drift_level: 0.0,
+            open_phase_ratio: 0.7,
+            breathiness_db: -99.0,
+            tilt_db: 0.0,
+            gain_db: f64::NAN,
+            agc_rms_level: rms,
+            nasal_formant_freq: 0.0,
+            nasal_formant_bw: 0.0,
+            oral_formant_freq,
+            oral_formant_bw,
+            cascade_enabled: true,
+            cascade_voicing_db: if is_voiced { 0.0 } else { -99.0 },
+            cascade_aspiration_db: if is_voiced || is_silent { -99.0 } else { 0.0 },
+            cascade_aspiration_mod: 0.0,
+            nasal_antiformant_freq: 0.0,
+            nasal_antiformant_bw: 0.0,
+            parallel_enabled: false,
+            parallel_voicing_db: -99.0,
+            parallel_aspiration_db: -99.0,
+            parallel_aspiration_mod: 0.0,
+            frication_db: -99.0,
+            frication_mod: 0.0,
+            parallel_bypass_db: -99.0,
+            nasal_formant_db: -99.0,
+            oral_formant_db: vec![-99.0; MAX_ORAL_FORMANTS],
+        });
+    }
+    Ok(frames)
+}
+
+/// Returns the LPC coefficients `[1, a1, .. ap]` of a signal, using the autocorrelation method
+/// and the Levinson-Durbin recursion. The prediction error filter is `A(z) = 1 + a1 z^-1 + .. + ap z^-p`.
+///
+/// A signal with zero energy results in `A(z) = 1`.
+///
+/// # Errors
+///
+/// Returns an error if the signal contains values that are not finite.
+pub fn get_lpc_coefficients(signal: &[f64], order: usize) -> Result<Vec<f64>, &'static str> {
+    if signal.iter().any(|x| !x.is_finite()) {
+        return Err("Signal contains values that are not finite.");
+    }
+    let r: Vec<f64> = (0..=order)
+        .map(|lag| {
+            signal
+                .iter()
+                .zip(signal.iter().skip(lag))
+                .map(|(x1, x2)| x1 * x2)
+                .sum()
+        })
+        .collect();
+    let mut a = vec![0.0; order + 1];
+    a[0] = 1.0;
+    let mut error = r[0];
+    for i in 1..=order {
+        if error <= 0.0 {
+            break;
+        }
+        let acc: f64 = (0..i).map(|j| a[j] * r[i - j]).sum();
+        let k = -acc / error;
+        let previous = a.clone();
+        for j in 1..i {
+            a[j] = previous[j] + k * previous[i - j];
+        }
+        a[i] = k;
+        error *= 1.0 - k * k;
+    }
+    Ok(a)
+}
+
+/// Returns the windowed segment of the signal centered at a sample position, zero-padded at the edges.
+fn get_segment(signal: &[f64], center: usize, window: &[f64]) -> Vec<f64> {
+    let half = window.len() / 2;
+    window
+        .iter()
+        .enumerate()
+        .map(|(i, w)| {
+            (center + i)
+                .checked_sub(half)
+                .and_then(|pos| signal.get(pos))
+                .map_or(0.0, |x| x * w)
+        })
+        .collect()
+}
+
+fn get_pre_emphasized(samples: &[f64], coefficient: f64) -> Vec<f64> {
+    let mut previous = 0.0;
+    samples
+        .iter()
+        .map(|x| {
+            let y = x - coefficient * previous;
+            previous = *x;
+            y
+        })
+        .collect()
+}
+
+fn get_rms(buf: &[f64]) -> f64 {
+    if buf.is_empty() {
+        return 0.0;
+    }
+    sqrt(buf.iter().map(|x| x * x).sum::<f64>() / buf.len() as f64)
+}
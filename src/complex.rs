@@ -0,0 +1,98 @@
+//! A minimal complex number type, used to evaluate transfer functions in the z-plane.
+
+use crate::math::{atan2, cos, sin, sqrt};
+use core::ops::{Add, Div, Mul, Neg, Sub};
+
+/// A complex number with `f64` parts.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Complex {
+    /// Real part.
+    pub re: f64,
+    /// Imaginary part.
+    pub im: f64,
+}
+
+impl Complex {
+    #[must_use]
+    pub const fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+
+    /// Returns the complex number with magnitude 1 and the specified angle.
+    #[must_use]
+    pub fn expj(phi: f64) -> Self {
+        Self::new(cos(phi), sin(phi))
+    }
+
+    /// Returns the complex number with the specified magnitude and angle.
+    #[must_use]
+    pub fn from_polar(r: f64, phi: f64) -> Self {
+        Self::new(r * cos(phi), r * sin(phi))
+    }
+
+    /// Returns the magnitude.
+    #[must_use]
+    pub fn abs(self) -> f64 {
+        sqrt(self.re * self.re + self.im * self.im)
+    }
+
+    /// Returns the angle, in the range -PI to PI.
+    #[must_use]
+    pub fn arg(self) -> f64 {
+        atan2(self.im, self.re)
+    }
+
+    /// Returns the complex conjugate.
+    #[must_use]
+    pub fn conj(self) -> Self {
+        Self::new(self.re, -self.im)
+    }
+}
+
+impl From<f64> for Complex {
+    fn from(re: f64) -> Self {
+        Self::new(re, 0.0)
+    }
+}
+
+impl Neg for Complex {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::new(-self.re, -self.im)
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+impl Div for Complex {
+    type Output = Self;
+    fn div(self, other: Self) -> Self {
+        let d = other.re * other.re + other.im * other.im;
+        Self::new(
+            (self.re * other.re + self.im * other.im) / d,
+            (self.im * other.re - self.re * other.im) / d,
+        )
+    }
+}
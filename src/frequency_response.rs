@@ -0,0 +1,186 @@
+//! Evaluation of transfer functions on the unit circle of the z-plane.
+
+use crate::complex::Complex;
+use crate::klatt::{
+    get_system_transfer_function_coefficients, get_vocal_tract_transfer_function_coefficients,
+    FrameParms, MainParms,
+};
+use crate::math::{cos, log10, pow};
+use crate::{Polynomial, RationalFunction};
+use alloc::vec::Vec;
+use core::f64::consts::PI;
+
+/// The response of a filter at a single frequency.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrequencyResponsePoint {
+    /// Frequency in Hz.
+    pub frequency: f64,
+    /// Magnitude in dB. A magnitude of 0 is returned as negative infinity.
+    pub magnitude_db: f64,
+    /// Phase in radians, in the range -PI to PI.
+    pub phase: f64,
+}
+
+/// A set of frequencies at which a frequency response is evaluated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FrequencyGrid {
+    /// Equally spaced frequencies from `start` to `end` (both inclusive), in Hz.
+    Linear { start: f64, end: f64, points: usize },
+    /// Logarithmically spaced frequencies from `start` to `end` (both inclusive), in Hz.
+    /// `start` must be above 0.
+    Logarithmic { start: f64, end: f64, points: usize },
+}
+
+impl FrequencyGrid {
+    /// Returns the frequencies of the grid.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bounds are not finite, or if the start of a logarithmic grid is not positive.
+    pub fn frequencies(&self) -> Result<Vec<f64>, &'static str> {
+        match *self {
+            FrequencyGrid::Linear { start, end, points } => {
+                if !start.is_finite() || !end.is_finite() {
+                    return Err("Invalid frequency grid bounds.");
+                }
+                Ok((0..points)
+                    .map(|i| start + (end - start) * get_grid_fraction(i, points))
+                    .collect())
+            }
+            FrequencyGrid::Logarithmic { start, end, points } => {
+                if !start.is_finite() || !end.is_finite() || start <= 0.0 || end <= 0.0 {
+                    return Err("Invalid frequency grid bounds.");
+                }
+                Ok((0..points)
+                    .map(|i| start * pow(end / start, get_grid_fraction(i, points)))
+                    .collect())
+            }
+        }
+    }
+}
+
+/// Returns the relative position of a grid point. A grid with a single point only contains the start.
+fn get_grid_fraction(i: usize, points: usize) -> f64 {
+    if points <= 1 {
+        0.0
+    } else {
+        i as f64 / (points - 1) as f64
+    }
+}
+
+/// Evaluates a transfer function at the specified frequencies.
+///
+/// ### params
+///
+/// ```text
+/// trans:       The transfer function, a rational function in z^-1.
+/// sample_rate: Sample rate of the filter in Hz.
+/// frequencies: Frequencies in Hz.
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the sample rate is 0.
+pub fn evaluate_frequency_response(
+    trans: &RationalFunction,
+    sample_rate: usize,
+    frequencies: &[f64],
+) -> Result<Vec<FrequencyResponsePoint>, &'static str> {
+    evaluate_response(sample_rate, frequencies, |z1| trans.evaluate_complex(z1))
+}
+
+/// Evaluates a transfer function, given as a function of `z^-1`, at the specified frequencies.
+pub(crate) fn evaluate_response(
+    sample_rate: usize,
+    frequencies: &[f64],
+    trans: impl Fn(Complex) -> Complex,
+) -> Result<Vec<FrequencyResponsePoint>, &'static str> {
+    if sample_rate == 0 {
+        return Err("Invalid sample rate.");
+    }
+    Ok(frequencies
+        .iter()
+        .map(|&frequency| {
+            let w = 2.0 * PI * frequency / sample_rate as f64;
+            let z1 = Complex::expj(-w); // z^-1
+            let h = trans(z1);
+            FrequencyResponsePoint {
+                frequency,
+                magnitude_db: 20.0 * log10(h.abs()),
+                phase: h.arg(),
+            }
+        })
+        .collect())
+}
+
+/// Returns the frequency response of the overall filter, as described by
+/// `get_vocal_tract_transfer_function_coefficients`, at the specified frequencies.
+///
+/// # Errors
+///
+/// Any invalid parameters will return a static str explaining the invalid param.
+pub fn get_vocal_tract_frequency_response(
+    m_parms: &MainParms,
+    f_parms: &FrameParms,
+    frequencies: &[f64],
+) -> Result<Vec<FrequencyResponsePoint>, &'static str> {
+    let trans = get_vocal_tract_transfer_function_coefficients(m_parms, f_parms)?;
+    evaluate_frequency_response(&trans, m_parms.sample_rate, frequencies)
+}
+
+/// Returns the frequency response of the whole synthesizer, including the glottal source, as described by
+/// `get_system_transfer_function_coefficients`, at the specified frequencies.
+///
+/// # Errors
+///
+/// Any invalid parameters will return a static str explaining the invalid param.
+pub fn get_system_frequency_response(
+    m_parms: &MainParms,
+    f_parms: &FrameParms,
+    frequencies: &[f64],
+) -> Result<Vec<FrequencyResponsePoint>, &'static str> {
+    let trans = get_system_transfer_function_coefficients(m_parms, f_parms)?;
+    evaluate_frequency_response(&trans, m_parms.sample_rate, frequencies)
+}
+
+/// Returns the spectrum of a signal segment at the specified frequencies,
+/// computed with a Hann window and a discrete-time Fourier transform.
+///
+/// The magnitude is scaled so that a sinusoid with an amplitude of 1 results in 0 dB at its frequency.
+/// For a voiced signal, the spectrum is best evaluated at the harmonics of F0, and the segment
+/// should contain several periods.
+///
+/// ### params
+///
+/// ```text
+/// samples:     The signal segment.
+/// sample_rate: Sample rate of the signal in Hz.
+/// frequencies: Frequencies in Hz.
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the sample rate is 0 or if the segment is empty.
+pub fn get_signal_spectrum(
+    samples: &[f64],
+    sample_rate: usize,
+    frequencies: &[f64],
+) -> Result<Vec<FrequencyResponsePoint>, &'static str> {
+    if samples.is_empty() {
+        return Err("Empty signal.");
+    }
+    let n = samples.len();
+    let window: Vec<f64> = (0..n)
+        .map(|i| 0.5 - 0.5 * cos(2.0 * PI * (i as f64 + 0.5) / n as f64))
+        .collect();
+    let scale = 2.0 / window.iter().sum::<f64>();
+    // The windowed signal is a FIR filter, its frequency response is the spectrum.
+    let fir = Polynomial::new(
+        samples
+            .iter()
+            .zip(&window)
+            .map(|(x, w)| x * w * scale)
+            .collect(),
+    );
+    evaluate_response(sample_rate, frequencies, |z1| fir.evaluate_complex(z1))
+}
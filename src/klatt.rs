@@ -1,6 +1,8 @@
 use crate::math::{cos, exp, pow, round, sin, sqrt};
-use crate::{poly_real, BasicFilter};
+use crate::pcm::SampleConverter;
+use crate::{BasicFilter, Polynomial, RationalFunction, SecondOrderSection, VocalTractSections};
 use alloc::{vec, vec::Vec};
+use core::borrow::Borrow;
 use core::f64::consts::PI;
 use core::{
     cmp::PartialEq, option::Option, option::Option::None, option::Option::Some, result::Result,
@@ -10,6 +12,70 @@ use rand::Rng;
 
 //--- Filters ------------------------------------------------------------------
 
+/// The coefficients of a first or second order filter, including its passthrough and mute state.
+/// Used to crossfade between two filter settings.
+#[derive(Clone, Copy)]
+struct FilterCoefficients {
+    a: f64,
+    b: f64,
+    c: f64,
+    passthrough: bool,
+    muted: bool,
+}
+impl FilterCoefficients {
+    /// Returns the plain `(a, b, c)` coefficients.
+    /// A passthrough filter is equivalent to `(1, 0, 0)` and a muted filter to `(0, 0, 0)`.
+    fn resolve(&self) -> (f64, f64, f64) {
+        if self.passthrough {
+            (1.0, 0.0, 0.0)
+        } else if self.muted {
+            (0.0, 0.0, 0.0)
+        } else {
+            (self.a, self.b, self.c)
+        }
+    }
+
+    /// Returns the coefficients of the inverse filter.
+    /// The inverse of a resonator is an anti-resonator and vice versa, both use the same coefficient form.
+    /// A muted filter cannot be inverted, its inverse is muted as well.
+    fn invert(&self) -> Self {
+        let (a, b, c) = self.resolve();
+        if a == 0.0 {
+            return FilterCoefficients {
+                a: 0.0,
+                b: 0.0,
+                c: 0.0,
+                passthrough: false,
+                muted: true,
+            };
+        }
+        FilterCoefficients {
+            a: 1.0 / a,
+            b: -b / a,
+            c: -c / a,
+            passthrough: self.passthrough,
+            muted: false,
+        }
+    }
+
+    /// Linearly interpolates between two coefficient sets.
+    /// `t = 0` returns `self`, `t = 1` returns `other`.
+    ///
+    /// For the resonators this is safe:
+    /// the region of stable `(b, c)` pairs is convex, so every intermediate filter is stable as well.
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        let (a1, b1, c1) = self.resolve();
+        let (a2, b2, c2) = other.resolve();
+        FilterCoefficients {
+            a: a1 + (a2 - a1) * t,
+            b: b1 + (b2 - b1) * t,
+            c: c1 + (c2 - c1) * t,
+            passthrough: false,
+            muted: false,
+        }
+    }
+}
+
 /// A first-order IIR LP filter.
 ///
 /// # Formulas:
@@ -71,16 +137,18 @@ struct LpFilter1 {
     muted: bool,
 }
 impl BasicFilter for LpFilter1 {
-    /// Returns the polynomial coefficients of the filter transfer function in the z-plane.
-    /// The returned array contains the top and bottom coefficients of the rational fraction, ordered in ascending powers.
-    fn get_transfer_function_coefficients(&self) -> Vec<Vec<f64>> {
+    /// Returns the filter transfer function in the z-plane, as a rational function in `z^-1`.
+    fn get_transfer_function_coefficients(&self) -> RationalFunction {
         if self.passthrough {
-            return vec![vec![1.0], vec![1.0]];
+            return RationalFunction::constant(1.0);
         }
         if self.muted {
-            return vec![vec![0.0], vec![1.0]];
+            return RationalFunction::constant(0.0);
         }
-        vec![vec![self.a], vec![1.0, -self.b]]
+        RationalFunction::new(
+            Polynomial::constant(self.a),
+            Polynomial::new(vec![1.0, -self.b]),
+        )
     }
 
     /// Performs a filter step.
@@ -158,6 +226,32 @@ impl LpFilter1 {
         self.muted = true;
         self.y1 = 0.0;
     }
+
+    fn get_coefficients(&self) -> FilterCoefficients {
+        FilterCoefficients {
+            a: self.a,
+            b: self.b,
+            c: 0.0,
+            passthrough: self.passthrough,
+            muted: self.muted,
+        }
+    }
+
+    /// Replaces the filter coefficients without resetting the inner state.
+    fn set_coefficients(&mut self, coefficients: &FilterCoefficients) {
+        self.a = coefficients.a;
+        self.b = coefficients.b;
+        self.passthrough = coefficients.passthrough;
+        self.muted = coefficients.muted;
+    }
+
+    fn get_second_order_section(&self) -> SecondOrderSection {
+        let (a, b, _) = self.get_coefficients().resolve();
+        SecondOrderSection {
+            a1: -b,
+            ..SecondOrderSection::gain(a)
+        }
+    }
 }
 
 /// A Klatt resonator.
@@ -288,18 +382,48 @@ impl Resonator {
         self.a = peak_gain * (1.0 - self.r);
         Ok(())
     }
+
+    fn get_coefficients(&self) -> FilterCoefficients {
+        FilterCoefficients {
+            a: self.a,
+            b: self.b,
+            c: self.c,
+            passthrough: self.passthrough,
+            muted: self.muted,
+        }
+    }
+
+    /// Replaces the filter coefficients without resetting the inner state.
+    fn set_coefficients(&mut self, coefficients: &FilterCoefficients) {
+        self.a = coefficients.a;
+        self.b = coefficients.b;
+        self.c = coefficients.c;
+        self.passthrough = coefficients.passthrough;
+        self.muted = coefficients.muted;
+    }
+
+    fn get_second_order_section(&self) -> SecondOrderSection {
+        let (a, b, c) = self.get_coefficients().resolve();
+        SecondOrderSection {
+            a1: -b,
+            a2: -c,
+            ..SecondOrderSection::gain(a)
+        }
+    }
 }
 impl BasicFilter for Resonator {
-    /// Returns the polynomial coefficients of the filter transfer function in the z-plane.
-    /// The returned array contains the top and bottom coefficients of the rational fraction, ordered in ascending powers.
-    fn get_transfer_function_coefficients(&self) -> Vec<Vec<f64>> {
+    /// Returns the filter transfer function in the z-plane, as a rational function in `z^-1`.
+    fn get_transfer_function_coefficients(&self) -> RationalFunction {
         if self.passthrough {
-            return vec![vec![1.0], vec![1.0]];
+            return RationalFunction::constant(1.0);
         }
         if self.muted {
-            return vec![vec![0.0], vec![1.0]];
+            return RationalFunction::constant(0.0);
         }
-        vec![vec![self.a], vec![1.0, -self.b, -self.c]]
+        RationalFunction::new(
+            Polynomial::constant(self.a),
+            Polynomial::new(vec![1.0, -self.b, -self.c]),
+        )
     }
 
     /// Performs a filter step.
@@ -425,18 +549,48 @@ impl AntiResonator {
         self.x1 = 0.0;
         self.x2 = 0.0;
     }
+
+    fn get_coefficients(&self) -> FilterCoefficients {
+        FilterCoefficients {
+            a: self.a,
+            b: self.b,
+            c: self.c,
+            passthrough: self.passthrough,
+            muted: self.muted,
+        }
+    }
+
+    /// Replaces the filter coefficients without resetting the inner state.
+    fn set_coefficients(&mut self, coefficients: &FilterCoefficients) {
+        self.a = coefficients.a;
+        self.b = coefficients.b;
+        self.c = coefficients.c;
+        self.passthrough = coefficients.passthrough;
+        self.muted = coefficients.muted;
+    }
+
+    fn get_second_order_section(&self) -> SecondOrderSection {
+        let (a, b, c) = self.get_coefficients().resolve();
+        SecondOrderSection {
+            b1: b,
+            b2: c,
+            ..SecondOrderSection::gain(a)
+        }
+    }
 }
 impl BasicFilter for AntiResonator {
-    /// Returns the polynomial coefficients of the filter transfer function in the z-plane.
-    /// The returned array contains the top and bottom coefficients of the rational fraction, ordered in ascending powers.
-    fn get_transfer_function_coefficients(&self) -> Vec<Vec<f64>> {
+    /// Returns the filter transfer function in the z-plane, as a rational function in `z^-1`.
+    fn get_transfer_function_coefficients(&self) -> RationalFunction {
         if self.passthrough {
-            return vec![vec![1.0], vec![1.0]];
+            return RationalFunction::constant(1.0);
         }
         if self.muted {
-            return vec![vec![0.0], vec![1.0]];
+            return RationalFunction::constant(0.0);
         }
-        vec![vec![self.a, self.b, self.c], vec![1.0]]
+        RationalFunction::new(
+            Polynomial::new(vec![self.a, self.b, self.c]),
+            Polynomial::constant(1.0),
+        )
     }
     /// Performs a filter step.
     /// ### params
@@ -497,10 +651,9 @@ impl DifferencingFilter {
     }
 }
 impl BasicFilter for DifferencingFilter {
-    // Returns the polynomial coefficients of the filter transfer function in the z-plane.
-    // The returned array contains the top and bottom coefficients of the rational fraction, ordered in ascending powers.
-    fn get_transfer_function_coefficients(&self) -> Vec<Vec<f64>> {
-        vec![vec![1.0, -1.0], vec![1.0]]
+    // Returns the filter transfer function in the z-plane, as a rational function in `z^-1`.
+    fn get_transfer_function_coefficients(&self) -> RationalFunction {
+        RationalFunction::new(Polynomial::new(vec![1.0, -1.0]), Polynomial::constant(1.0))
     }
     /// Performs a filter step.
     /// ### params
@@ -688,7 +841,7 @@ impl NaturalGlottalSource {
 /// ```text
 ///    f0 = Fundamental frequency.
 ///    flutter_level = Flutter level between 0 and 1.
-///    time = Relative signal position in seconds.
+///    time = Relative signal position in seconds, including the fractional part.
 /// ```
 /// ### returns
 ///    Modulated fundamental frequency.
@@ -702,6 +855,50 @@ fn perform_frequency_modulation(f0: f64, flutter_level: f64, time: f64) -> f64 {
     f0 * (1.0 + a * flutter_level / 50.0)
 }
 
+/// Applies vibrato and a random-walk drift to the fundamental frequency.
+///
+/// ### params
+/// ```text
+///    f0 = Fundamental frequency.
+///    vibrato_rate = Vibrato rate in Hz.
+///    vibrato_depth = Peak vibrato deviation in semitones.
+///    drift = Current drift in semitones.
+///    time = Absolute signal position in seconds.
+/// ```
+/// ### returns
+///    Modulated fundamental frequency.
+fn perform_pitch_modulation(
+    f0: f64,
+    vibrato_rate: f64,
+    vibrato_depth: f64,
+    drift: f64,
+    time: f64,
+) -> f64 {
+    let mut semitones = drift;
+    if vibrato_depth > 0.0 && vibrato_rate > 0.0 {
+        semitones += vibrato_depth * sin(2.0 * PI * vibrato_rate * time);
+    }
+    if semitones == 0.0 {
+        return f0;
+    }
+    f0 * pow(2.0, semitones / 12.0)
+}
+
+/// Returns the linear tremolo gain factor.
+///
+/// ### params
+/// ```text
+///    tremolo_rate = Tremolo rate in Hz.
+///    tremolo_depth = Relative amplitude deviation, 0 .. 1.
+///    time = Absolute signal position in seconds.
+/// ```
+fn get_tremolo_gain(tremolo_rate: f64, tremolo_depth: f64, time: f64) -> f64 {
+    if tremolo_depth <= 0.0 || tremolo_rate <= 0.0 {
+        return 1.0;
+    }
+    1.0 + tremolo_depth * sin(2.0 * PI * tremolo_rate * time)
+}
+
 /// Convert a dB value into a linear value.
 /// dB values of -99 and below or NaN are converted to 0.
 fn db_to_lin(db: f64) -> f64 {
@@ -720,6 +917,79 @@ pub enum GlottalSourceType {
     Noise,
 }
 
+/// Determines when the parameters of a new frame become active.
+pub enum FrameSwitchPolicy {
+    /// New frame parameters are only activated at the start of the next F0 period.
+    /// This avoids glitches, but a frame may start up to one F0 period late.
+    PeriodSynchronous,
+    /// New frame parameters are activated at the first sample of the frame.
+    /// The filter coefficients and levels are linearly crossfaded from the old to the new values
+    /// over `crossfade_length` samples; 0 switches abruptly.
+    SampleAccurate { crossfade_length: usize },
+}
+
+/// A fundamental frequency contour within a frame.
+/// Used instead of the constant `FrameParms::f0`, it is evaluated at the start of each F0 period.
+#[derive(Clone, PartialEq)]
+pub enum F0Contour {
+    /// Linear ramp from `start` to `end` Hz over the duration of the frame.
+    Linear { start: f64, end: f64 },
+    /// Exponential ramp from `start` to `end` Hz over the duration of the frame,
+    /// i.e. linear on a logarithmic frequency scale.
+    /// Falls back to a linear ramp if `start` or `end` is not positive.
+    Exponential { start: f64, end: f64 },
+    /// List of `(time, f0)` breakpoints, with the time in seconds relative to the start of the frame,
+    /// in ascending order. The f0 is interpolated linearly between the breakpoints
+    /// and held constant before the first and after the last one.
+    Breakpoints(Vec<(f64, f64)>),
+}
+impl F0Contour {
+    /// Returns the f0 in Hz at a time relative to the start of the frame.
+    /// ### params
+    /// ```text
+    ///    time = Time in seconds, relative to the start of the frame.
+    ///    duration = Frame duration in seconds.
+    /// ```
+    /// ### returns
+    ///    The f0 in Hz, or `None` for an empty list of breakpoints.
+    #[must_use]
+    pub fn value_at(&self, time: f64, duration: f64) -> Option<f64> {
+        let t = if duration > 0.0 {
+            (time / duration).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        match self {
+            F0Contour::Linear { start, end } => Some(start + (end - start) * t),
+            F0Contour::Exponential { start, end } => {
+                if *start > 0.0 && *end > 0.0 {
+                    Some(start * pow(end / start, t))
+                } else {
+                    Some(start + (end - start) * t)
+                }
+            }
+            F0Contour::Breakpoints(points) => interpolate_breakpoints(points, time),
+        }
+    }
+}
+
+/// Interpolates linearly within a list of `(time, value)` breakpoints, ordered by ascending time.
+/// The value is held constant before the first and after the last breakpoint.
+/// Returns `None` for an empty list.
+pub(crate) fn interpolate_breakpoints(points: &[(f64, f64)], time: f64) -> Option<f64> {
+    let (first, last) = (points.first()?, points.last()?);
+    if time <= first.0 {
+        return Some(first.1);
+    }
+    if time >= last.0 {
+        return Some(last.1);
+    }
+    let i = points.partition_point(|(t, _)| *t <= time);
+    let (t1, v1) = points[i - 1];
+    let (t2, v2) = points[i];
+    Some(v1 + (v2 - v1) * (time - t1) / (t2 - t1))
+}
+
 pub const MAX_ORAL_FORMANTS: usize = 6;
 
 /// Parameters for the whole sound.
@@ -727,17 +997,43 @@ pub struct MainParms {
     /// sample rate in Hz
     pub sample_rate: usize,
     pub glottal_source_type: GlottalSourceType,
+    /// when the parameters of a new frame become active
+    pub frame_switch_policy: FrameSwitchPolicy,
+}
+
+impl Default for MainParms {
+    /// 44100 Hz with the natural glottal source, and frames that are switched at the start of an F0
+    /// period.
+    fn default() -> Self {
+        Self {
+            sample_rate: 44100,
+            glottal_source_type: GlottalSourceType::Natural,
+            frame_switch_policy: FrameSwitchPolicy::PeriodSynchronous,
+        }
+    }
 }
 
 /// Parameters for a sound frame.
-#[derive(PartialEq)]
+#[derive(Clone, PartialEq)]
 pub struct FrameParms {
     /// frame duration in seconds
-    pub duration: usize,
+    pub duration: f64,
     /// fundamental frequency in Hz
     pub f0: f64,
+    /// fundamental frequency contour, replaces `f0` if set
+    pub f0_contour: Option<F0Contour>,
     /// F0 flutter level, 0 .. 1, typically 0.25
     pub flutter_level: f64,
+    /// vibrato rate in Hz
+    pub vibrato_rate: f64,
+    /// vibrato depth, peak F0 deviation in semitones, 0 = no vibrato
+    pub vibrato_depth: f64,
+    /// tremolo rate in Hz
+    pub tremolo_rate: f64,
+    /// tremolo depth, relative amplitude deviation 0 .. 1, 0 = no tremolo
+    pub tremolo_depth: f64,
+    /// maximum F0 deviation of the random-walk drift in semitones, 0 = no drift
+    pub drift_level: f64,
     /// relative length of the open phase of the glottis, 0 .. 1, typically 0.7
     pub open_phase_ratio: f64,
     /// breathiness in voicing (turbulence) in dB, positive to amplify or negative to attenuate
@@ -794,6 +1090,7 @@ pub struct FrameParms {
 
 /// Variables of the currently active frame.
 #[allow(clippy::struct_field_names)]
+#[derive(Clone, Copy)]
 struct FrameState {
     /// linear breathiness level
     pub breathiness_lin: f64,
@@ -829,6 +1126,122 @@ impl FrameState {
             parallel_bypass_lin: 0.0,
         }
     }
+
+    pub fn from_frame_parms(f_parms: &FrameParms) -> Self {
+        let db = if f_parms.gain_db.is_finite() {
+            f_parms.gain_db
+        } else {
+            0.0
+        };
+        FrameState {
+            breathiness_lin: db_to_lin(f_parms.breathiness_db),
+            gain_lin: db_to_lin(db),
+            cascade_voicing_lin: db_to_lin(f_parms.cascade_voicing_db),
+            cascade_aspiration_lin: db_to_lin(f_parms.cascade_aspiration_db),
+            parallel_voicing_lin: db_to_lin(f_parms.parallel_voicing_db),
+            parallel_aspiration_lin: db_to_lin(f_parms.parallel_aspiration_db),
+            frication_lin: db_to_lin(f_parms.frication_db),
+            parallel_bypass_lin: db_to_lin(f_parms.parallel_bypass_db),
+        }
+    }
+
+    /// Linearly interpolates between two frame states.
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        let lerp = |v1: f64, v2: f64| v1 + (v2 - v1) * t;
+        FrameState {
+            breathiness_lin: lerp(self.breathiness_lin, other.breathiness_lin),
+            gain_lin: lerp(self.gain_lin, other.gain_lin),
+            cascade_voicing_lin: lerp(self.cascade_voicing_lin, other.cascade_voicing_lin),
+            cascade_aspiration_lin: lerp(self.cascade_aspiration_lin, other.cascade_aspiration_lin),
+            parallel_voicing_lin: lerp(self.parallel_voicing_lin, other.parallel_voicing_lin),
+            parallel_aspiration_lin: lerp(
+                self.parallel_aspiration_lin,
+                other.parallel_aspiration_lin,
+            ),
+            frication_lin: lerp(self.frication_lin, other.frication_lin),
+            parallel_bypass_lin: lerp(self.parallel_bypass_lin, other.parallel_bypass_lin),
+        }
+    }
+}
+
+/// The frame dependent levels and filter coefficients of the generator.
+/// Used to crossfade between two frames with `FrameSwitchPolicy::SampleAccurate`.
+#[derive(Clone, Copy)]
+struct FrameSnapshot {
+    f_state: FrameState,
+    tilt_filter: FilterCoefficients,
+    nasal_formant_casc: FilterCoefficients,
+    nasal_antiformant_casc: FilterCoefficients,
+    oral_formant_casc: [FilterCoefficients; MAX_ORAL_FORMANTS],
+    nasal_formant_par: FilterCoefficients,
+    oral_formant_par: [FilterCoefficients; MAX_ORAL_FORMANTS],
+}
+impl FrameSnapshot {
+    /// Computes the levels and filter coefficients for a frame, without touching any filter state.
+    pub fn new(m_parms: &MainParms, f_parms: &FrameParms) -> Result<Self, &'static str> {
+        let mut tilt_filter = LpFilter1::new(m_parms.sample_rate);
+        set_tilt_filter(&mut tilt_filter, f_parms.tilt_db)?;
+        let mut nasal_formant_casc = Resonator::new(m_parms.sample_rate);
+        set_nasal_formant_casc(&mut nasal_formant_casc, f_parms)?;
+        let mut nasal_antiformant_casc = AntiResonator::new(m_parms.sample_rate);
+        set_nasal_antiformant_casc(&mut nasal_antiformant_casc, f_parms)?;
+        let mut nasal_formant_par = Resonator::new(m_parms.sample_rate);
+        set_nasal_formant_par(&mut nasal_formant_par, f_parms)?;
+        let mut snapshot = FrameSnapshot {
+            f_state: FrameState::from_frame_parms(f_parms),
+            tilt_filter: tilt_filter.get_coefficients(),
+            nasal_formant_casc: nasal_formant_casc.get_coefficients(),
+            nasal_antiformant_casc: nasal_antiformant_casc.get_coefficients(),
+            oral_formant_casc: [nasal_formant_casc.get_coefficients(); MAX_ORAL_FORMANTS],
+            nasal_formant_par: nasal_formant_par.get_coefficients(),
+            oral_formant_par: [nasal_formant_par.get_coefficients(); MAX_ORAL_FORMANTS],
+        };
+        for i in 0..MAX_ORAL_FORMANTS {
+            let mut oral_formant_casc = Resonator::new(m_parms.sample_rate);
+            set_oral_formant_casc(&mut oral_formant_casc, f_parms, i)?;
+            snapshot.oral_formant_casc[i] = oral_formant_casc.get_coefficients();
+            let mut oral_formant_par = Resonator::new(m_parms.sample_rate);
+            set_oral_formant_par(&mut oral_formant_par, m_parms, f_parms, i)?;
+            snapshot.oral_formant_par[i] = oral_formant_par.get_coefficients();
+        }
+        Ok(snapshot)
+    }
+
+    /// Linearly interpolates between two snapshots.
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        let mut snapshot = FrameSnapshot {
+            f_state: self.f_state.interpolate(&other.f_state, t),
+            tilt_filter: self.tilt_filter.interpolate(&other.tilt_filter, t),
+            nasal_formant_casc: self
+                .nasal_formant_casc
+                .interpolate(&other.nasal_formant_casc, t),
+            nasal_antiformant_casc: self
+                .nasal_antiformant_casc
+                .interpolate(&other.nasal_antiformant_casc, t),
+            oral_formant_casc: self.oral_formant_casc,
+            nasal_formant_par: self
+                .nasal_formant_par
+                .interpolate(&other.nasal_formant_par, t),
+            oral_formant_par: self.oral_formant_par,
+        };
+        for i in 0..MAX_ORAL_FORMANTS {
+            snapshot.oral_formant_casc[i] =
+                self.oral_formant_casc[i].interpolate(&other.oral_formant_casc[i], t);
+            snapshot.oral_formant_par[i] =
+                self.oral_formant_par[i].interpolate(&other.oral_formant_par[i], t);
+        }
+        snapshot
+    }
+}
+
+/// A running crossfade from the parameters of the previous frame to the current frame.
+struct Crossfade {
+    from: FrameSnapshot,
+    to: FrameSnapshot,
+    /// number of samples already crossfaded
+    position: usize,
+    /// crossfade length in samples
+    length: usize,
 }
 
 /// Variables of the currently active F0 period (aka glottal period).
@@ -836,7 +1249,9 @@ impl FrameState {
 struct PeriodState {
     /// modulated fundamental frequency for this period, in Hz, or 0
     pub f0: f64,
-    /// period length in samples
+    /// false while unvoiced (f0 <= 0); there are no F0 periods then
+    pub voiced: bool,
+    /// period length in samples, 0 while unvoiced
     period_length: usize,
     /// open glottis phase length in samples
     pub open_phase_length: usize,
@@ -852,49 +1267,39 @@ impl PeriodState {
     pub fn new() -> Self {
         PeriodState {
             f0: 0.0,
+            voiced: false,
             period_length: 0,
             open_phase_length: 0,
             position_in_period: 0,
             lp_noise: 0,
         }
     }
+
+    /// Returns `true` if the noise sources are currently amplitude modulated.
+    /// This is the case in the second half of a voiced F0 period.
+    /// Without voicing there is no glottal cycle to modulate with, so the noise is unmodulated.
+    fn is_noise_modulated(&self) -> bool {
+        self.voiced && self.position_in_period >= self.period_length / 2
+    }
 }
 
-/// Sound generator controller.
-pub struct Generator<'a, R> {
-    /// main parameters
-    m_parms: &'a MainParms,
-    /// currently active frame parameters
-    f_parms: Option<&'a FrameParms>,
-    /// new frame parameters for start of next F0 period
-    new_f_parms: Option<&'a FrameParms>,
-    /// frame variables
-    f_state: FrameState,
-    /// F0 period state variables
-    p_state: Option<PeriodState>,
-    /// current absolute sample position
-    abs_position: usize,
+/// Position of a frame within the generated signal.
+#[derive(Clone, Copy)]
+struct FrameTiming {
+    /// absolute sample position of the start of the frame
+    start: usize,
+    /// frame length in samples
+    length: usize,
+}
+
+/// The filters of the vocal tract: the spectral tilt filter, the cascade and parallel branches
+/// and the output low-pass filter.
+/// The glottal source, the noise sources and the levels of the frame are not part of it.
+struct VocalTract {
     /// spectral tilt filter
     tilt_filter: LpFilter1,
     /// output low-pass filter
     output_lp_filter: Resonator,
-    /// random value for flutter time offset
-    flutter_time_offset: usize,
-
-    // Glottal source:
-    impulsive_g_source: Option<ImpulsiveGlottalSource>,
-    natural_g_source: Option<NaturalGlottalSource>,
-    /// function which returns the next glottal source signal sample value
-    glottal_source: fn(&mut Generator<R>) -> f64,
-
-    // Noise sources:
-    // (We use independent noise sources to avoid cancellation effects of correlated signals.)
-    /// noise source for aspiration in cascade branch
-    aspiration_source_casc: LpNoiseSource<R>,
-    /// noise source for aspiration in parallel branch
-    aspiration_source_par: LpNoiseSource<R>,
-    /// noise source for frication in parallel branch
-    frication_source_par: LpNoiseSource<R>,
 
     // Cascade branch variables:
     /// nasal formant filter for cascade branch
@@ -911,31 +1316,12 @@ pub struct Generator<'a, R> {
     oral_formant_par: Vec<Resonator>,
     /// differencing filter for the parallel branch
     differencing_filter_par: DifferencingFilter,
-    /// random number generator function
-    rng: R,
 }
-impl<'a, R: Rng + Clone> Generator<'a, R> {
-    pub fn new(m_parms: &MainParms, mut rng: R) -> Result<Generator<R>, &'static str> {
-        let mut generator = Generator {
-            m_parms,
-            f_state: FrameState::new(),
-            abs_position: 0,
+impl VocalTract {
+    pub fn new(m_parms: &MainParms) -> Result<Self, &'static str> {
+        let mut vocal_tract = VocalTract {
             tilt_filter: LpFilter1::new(m_parms.sample_rate),
-            flutter_time_offset: rng.random_range(0..=1000),
             output_lp_filter: Resonator::new(m_parms.sample_rate),
-            f_parms: None,
-            new_f_parms: None,
-            p_state: None,
-
-            // Glottal source:
-            impulsive_g_source: None,
-            natural_g_source: None,
-            glottal_source: |_g: &mut Generator<R>| 0.0,
-
-            // Create noise sources:
-            aspiration_source_casc: LpNoiseSource::new(m_parms.sample_rate, rng.clone())?,
-            aspiration_source_par: LpNoiseSource::new(m_parms.sample_rate, rng.clone())?,
-            frication_source_par: LpNoiseSource::new(m_parms.sample_rate, rng.clone())?,
 
             // Initialize cascade branch variables:
             nasal_formant_casc: Resonator::new(m_parms.sample_rate),
@@ -946,113 +1332,53 @@ impl<'a, R: Rng + Clone> Generator<'a, R> {
             nasal_formant_par: Resonator::new(m_parms.sample_rate),
             oral_formant_par: Vec::with_capacity(MAX_ORAL_FORMANTS),
             differencing_filter_par: DifferencingFilter::new(),
-            rng,
         };
 
-        generator
+        vocal_tract
             .output_lp_filter
             .set(0.0, (m_parms.sample_rate as f64) / 2.0, None)?;
 
-        generator.init_glottal_source();
-
         for _ in 0..MAX_ORAL_FORMANTS {
-            generator
+            vocal_tract
                 .oral_formant_casc
                 .push(Resonator::new(m_parms.sample_rate));
-            generator
+            vocal_tract
                 .oral_formant_par
                 .push(Resonator::new(m_parms.sample_rate));
         }
 
-        Ok(generator)
+        Ok(vocal_tract)
     }
 
-    /// Generates a frame of the sound.
-    /// The length of the frame is specified by `outBuf.length` and `fParms.duration` is ignored.
-    pub fn generate_frame(
+    /// Sets the filter coefficients for a frame, without resetting the filter state.
+    pub fn set_frame_parameters(
         &mut self,
-        f_parms: &'a FrameParms,
-        out_buf: &mut [f64],
+        m_parms: &MainParms,
+        f_parms: &FrameParms,
     ) -> Result<(), &'static str> {
-        if let Some(parms) = self.f_parms {
-            if parms == f_parms {
-                return Err("FrameParms structure must not be re-used.");
-            }
-        }
-
-        self.new_f_parms = Some(f_parms);
-        for out_pos in &mut *out_buf {
-            match &self.p_state {
-                Some(p_state) => {
-                    if p_state.position_in_period >= p_state.period_length {
-                        self.start_new_period()?;
-                    }
-                }
-                None => self.start_new_period()?,
-            }
+        set_tilt_filter(&mut self.tilt_filter, f_parms.tilt_db)?;
 
-            *out_pos = self.compute_next_output_signal_sample();
-            self.p_state.as_mut().unwrap().position_in_period += 1;
-            self.abs_position += 1;
+        // Adjust cascade branch:
+        set_nasal_formant_casc(&mut self.nasal_formant_casc, f_parms)?;
+        set_nasal_antiformant_casc(&mut self.nasal_antiformant_casc, f_parms)?;
+        for i in 0..MAX_ORAL_FORMANTS {
+            set_oral_formant_casc(&mut self.oral_formant_casc[i], f_parms, i)?;
         }
 
-        // automatic gain control (AGC)
-        if f_parms.gain_db.is_nan() {
-            adjust_signal_gain(out_buf, f_parms.agc_rms_level);
+        // Adjust parallel branch:
+        set_nasal_formant_par(&mut self.nasal_formant_par, f_parms)?;
+        for i in 0..MAX_ORAL_FORMANTS {
+            set_oral_formant_par(&mut self.oral_formant_par[i], m_parms, f_parms, i)?;
         }
-
         Ok(())
     }
 
-    fn compute_next_output_signal_sample(&mut self) -> f64 {
-        let glottan_source: fn(&mut Generator<R>) -> f64 = self.glottal_source;
-        let mut voice = glottan_source(self);
-
-        let f_parms = self.f_parms.unwrap();
-        let p_state = self.p_state.as_ref().unwrap();
-
-        // apply spectral tilt
-        voice = self.tilt_filter.step(voice);
-
-        // if within glottal open phase
-        if p_state.position_in_period < p_state.open_phase_length {
-            // add breathiness (turbulence)
-            voice += get_white_noise(&mut self.rng) * self.f_state.breathiness_lin;
-        }
-
-        let cascade_out = if f_parms.cascade_enabled {
-            self.compute_cascade_branch(voice)
-        } else {
-            0.0
-        };
-
-        let parallel_out = if f_parms.parallel_enabled {
-            self.compute_parallel_branch(voice)
-        } else {
-            0.0
-        };
-
-        let mut out = cascade_out + parallel_out;
-        out = self.output_lp_filter.step(out);
-        out *= self.f_state.gain_lin;
-        out
-    }
-
-    fn compute_cascade_branch(&mut self, voice: f64) -> f64 {
-        let f_parms = self.f_parms.unwrap();
-        let p_state = self.p_state.as_ref().unwrap();
-        let cascade_voice = voice * self.f_state.cascade_voicing_lin;
-
-        let current_aspiration_mod = if p_state.position_in_period >= p_state.period_length / 2 {
-            f_parms.cascade_aspiration_mod
-        } else {
-            0.0
-        };
-
-        let aspiration = self.aspiration_source_casc.get_next()
-            * self.f_state.cascade_aspiration_lin
-            * (1.0 - current_aspiration_mod);
-        let mut v = cascade_voice + aspiration;
+    /// Performs a step of the cascade branch.
+    /// ### params
+    /// ```text
+    ///    v = Input of the branch, the voicing and the aspiration.
+    /// ```
+    pub fn step_cascade(&mut self, mut v: f64) -> f64 {
         v = self.nasal_antiformant_casc.step(v);
         v = self.nasal_formant_casc.step(v);
         for i in 0..MAX_ORAL_FORMANTS {
@@ -1061,21 +1387,19 @@ impl<'a, R: Rng + Clone> Generator<'a, R> {
         v
     }
 
-    fn compute_parallel_branch(&mut self, voice: f64) -> f64 {
-        let f_parms = self.f_parms.unwrap();
-        let p_state = self.p_state.as_ref().unwrap();
-        let parallel_voice = voice * self.f_state.parallel_voicing_lin;
-
-        let current_aspiration_mod = if p_state.position_in_period >= p_state.period_length / 2 {
-            f_parms.parallel_aspiration_mod
-        } else {
-            0.0
-        };
-
-        let aspiration = self.aspiration_source_par.get_next()
-            * self.f_state.parallel_aspiration_lin
-            * (1.0 - current_aspiration_mod);
-        let source = parallel_voice + aspiration;
+    /// Performs a step of the parallel branch.
+    /// ### params
+    /// ```text
+    ///    source = Input of the branch, the voicing and the aspiration.
+    ///    frication_noise = Frication noise, added to the source difference.
+    ///    parallel_bypass_lin = Linear level of the bypass.
+    /// ```
+    pub fn step_parallel(
+        &mut self,
+        source: f64,
+        frication_noise: f64,
+        parallel_bypass_lin: f64,
+    ) -> f64 {
         let source_difference = self.differencing_filter_par.step(source);
         // Klatt (1980) states: "... using a first difference calculation to remove low-frequency energy from
         // the higher formants; this energy would otherwise distort the spectrum in the region of F1 during
@@ -1084,15 +1408,6 @@ impl<'a, R: Rng + Clone> Generator<'a, R> {
         // A better solution would probably be to use real band-pass filters instead of resonators for the formants
         // in the parallel branch. Then this differencing filter would not be necessary to protect the low frequencies
         // of the low formants.
-        let current_frication_mod = if p_state.position_in_period >= p_state.period_length / 2 {
-            f_parms.frication_mod
-        } else {
-            0.0
-        };
-
-        let frication_noise = self.frication_source_par.get_next()
-            * self.f_state.frication_lin
-            * (1.0 - current_frication_mod);
         let source2 = source_difference + frication_noise;
         let mut v = 0.0;
         v += self.nasal_formant_par.step(source); // nasal formant is directly applied to source
@@ -1103,82 +1418,473 @@ impl<'a, R: Rng + Clone> Generator<'a, R> {
             v += alternating_sign * self.oral_formant_par[i].step(source2);
         }
         // bypass is applied to source difference + frication
-        v += self.f_state.parallel_bypass_lin * source2;
+        v += parallel_bypass_lin * source2;
         v
     }
+}
+
+/// The inverse of the cascade branch and the output low-pass filter of the vocal tract,
+/// used for inverse filtering. The filters are applied in the reverse order.
+struct InverseVocalTract {
+    /// inverse of the output low-pass filter
+    output_lp_filter: AntiResonator,
+    /// inverse of the nasal formant filter
+    nasal_formant_casc: AntiResonator,
+    /// inverse of the nasal antiformant filter
+    nasal_antiformant_casc: Resonator,
+    /// inverses of the oral formant filters
+    oral_formant_casc: Vec<AntiResonator>,
+}
+impl InverseVocalTract {
+    pub fn new(m_parms: &MainParms) -> Self {
+        InverseVocalTract {
+            output_lp_filter: AntiResonator::new(m_parms.sample_rate),
+            nasal_formant_casc: AntiResonator::new(m_parms.sample_rate),
+            nasal_antiformant_casc: Resonator::new(m_parms.sample_rate),
+            oral_formant_casc: (0..MAX_ORAL_FORMANTS)
+                .map(|_| AntiResonator::new(m_parms.sample_rate))
+                .collect(),
+        }
+    }
+
+    /// Sets the filter coefficients to the inverses of the filters of a vocal tract,
+    /// without resetting the filter state.
+    pub fn set_frame_parameters(&mut self, vocal_tract: &VocalTract) {
+        self.output_lp_filter
+            .set_coefficients(&vocal_tract.output_lp_filter.get_coefficients().invert());
+        self.nasal_formant_casc
+            .set_coefficients(&vocal_tract.nasal_formant_casc.get_coefficients().invert());
+        self.nasal_antiformant_casc.set_coefficients(
+            &vocal_tract
+                .nasal_antiformant_casc
+                .get_coefficients()
+                .invert(),
+        );
+        for (inverse, filter) in self
+            .oral_formant_casc
+            .iter_mut()
+            .zip(&vocal_tract.oral_formant_casc)
+        {
+            inverse.set_coefficients(&filter.get_coefficients().invert());
+        }
+    }
+
+    /// Performs a step of the inverse filter, from the output of the vocal tract to the input
+    /// of the cascade branch.
+    pub fn step(&mut self, mut v: f64) -> f64 {
+        v = self.output_lp_filter.step(v);
+        for i in (0..MAX_ORAL_FORMANTS).rev() {
+            v = self.oral_formant_casc[i].step(v);
+        }
+        v = self.nasal_formant_casc.step(v);
+        self.nasal_antiformant_casc.step(v)
+    }
+}
+
+/// Sound generator controller.
+///
+/// Generates the sound frame by frame, so it can also be used for streaming.
+pub struct Generator<'a, R> {
+    /// main parameters
+    m_parms: &'a MainParms,
+    /// currently active frame parameters
+    f_parms: Option<FrameParms>,
+    /// new frame parameters for start of next F0 period
+    new_f_parms: Option<FrameParms>,
+    /// position of the currently active frame
+    frame_timing: FrameTiming,
+    /// position of the new frame
+    new_frame_timing: FrameTiming,
+    /// frame variables
+    f_state: FrameState,
+    /// F0 period state variables
+    p_state: Option<PeriodState>,
+    /// current absolute sample position
+    abs_position: usize,
+    /// filters of the vocal tract
+    vocal_tract: VocalTract,
+    /// random value for flutter time offset
+    flutter_time_offset: usize,
+    /// current position of the F0 random walk, -1 .. 1
+    drift: f64,
+    /// running crossfade between two frames, only used with `FrameSwitchPolicy::SampleAccurate`
+    crossfade: Option<Crossfade>,
+
+    // Glottal source:
+    impulsive_g_source: Option<ImpulsiveGlottalSource>,
+    natural_g_source: Option<NaturalGlottalSource>,
+    /// function which returns the next glottal source signal sample value
+    glottal_source: fn(&mut Generator<R>) -> f64,
+
+    // Noise sources:
+    // (We use independent noise sources to avoid cancellation effects of correlated signals.)
+    /// noise source for aspiration in cascade branch
+    aspiration_source_casc: LpNoiseSource<R>,
+    /// noise source for aspiration in parallel branch
+    aspiration_source_par: LpNoiseSource<R>,
+    /// noise source for frication in parallel branch
+    frication_source_par: LpNoiseSource<R>,
+
+    /// random number generator function
+    rng: R,
+}
+impl<R: Rng + Clone> Generator<'_, R> {
+    /// # Errors
+    ///
+    /// Returns a static str if there is a problem with the `m_parms` values.
+    pub fn new(m_parms: &MainParms, mut rng: R) -> Result<Generator<'_, R>, &'static str> {
+        let mut generator = Generator {
+            m_parms,
+            f_state: FrameState::new(),
+            abs_position: 0,
+            vocal_tract: VocalTract::new(m_parms)?,
+            flutter_time_offset: rng.random_range(0..=1000),
+            drift: 0.0,
+            f_parms: None,
+            new_f_parms: None,
+            frame_timing: FrameTiming {
+                start: 0,
+                length: 0,
+            },
+            new_frame_timing: FrameTiming {
+                start: 0,
+                length: 0,
+            },
+            p_state: None,
+            crossfade: None,
+
+            // Glottal source:
+            impulsive_g_source: None,
+            natural_g_source: None,
+            glottal_source: |_g: &mut Generator<R>| 0.0,
+
+            // Create noise sources:
+            aspiration_source_casc: LpNoiseSource::new(m_parms.sample_rate, rng.clone())?,
+            aspiration_source_par: LpNoiseSource::new(m_parms.sample_rate, rng.clone())?,
+            frication_source_par: LpNoiseSource::new(m_parms.sample_rate, rng.clone())?,
+            rng,
+        };
+
+        generator.init_glottal_source();
+
+        Ok(generator)
+    }
+
+    /// Generates a frame of the sound.
+    /// The length of the frame is specified by `out_buf.len()` and `f_parms.duration` is ignored.
+    /// The frame parameters are copied, so frames can be generated on the fly.
+    ///
+    /// # Errors
+    ///
+    /// Returns a static str if there is a problem with the `f_parms` values.
+    // the unwraps only access state that is always initialized before use
+    #[allow(clippy::missing_panics_doc)]
+    pub fn generate_frame(
+        &mut self,
+        f_parms: &FrameParms,
+        out_buf: &mut [f64],
+    ) -> Result<(), &'static str> {
+        self.new_f_parms = Some(f_parms.clone());
+        self.new_frame_timing = FrameTiming {
+            start: self.abs_position,
+            length: out_buf.len(),
+        };
+        if let FrameSwitchPolicy::SampleAccurate { crossfade_length } =
+            self.m_parms.frame_switch_policy
+        {
+            self.switch_frame_parameters(crossfade_length)?;
+        }
+        for out_pos in &mut *out_buf {
+            self.step_crossfade();
+            if self.needs_new_period() {
+                self.start_new_period()?;
+            }
+
+            *out_pos = self.compute_next_output_signal_sample();
+            self.p_state.as_mut().unwrap().position_in_period += 1;
+            self.abs_position += 1;
+        }
+
+        // automatic gain control (AGC)
+        if f_parms.gain_db.is_nan() {
+            adjust_signal_gain(out_buf, f_parms.agc_rms_level);
+        }
+
+        Ok(())
+    }
+
+    /// Generates a frame of the sound and converts it to another sample format, e.g. with a
+    /// `Quantizer` to 16 bit PCM or with `MuLaw` for telephony. See `generate_frame`.
+    ///
+    /// The frame is generated into `scratch_buf` first, because the automatic gain control needs
+    /// the whole frame. The scratch buffer can be reused for all frames, so no memory is allocated.
+    ///
+    /// ### params
+    ///
+    /// ```text
+    /// f_parms:     The frame parameters.
+    /// converter:   The converter, e.g. a `Quantizer`.
+    /// scratch_buf: A buffer for the unconverted samples, at least as long as `out_buf`.
+    /// out_buf:     The converted samples.
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a static str if the scratch buffer is too short or if there is a problem with the
+    /// `f_parms` values.
+    pub fn generate_converted_frame<T>(
+        &mut self,
+        f_parms: &FrameParms,
+        converter: &mut impl SampleConverter<T>,
+        scratch_buf: &mut [f64],
+        out_buf: &mut [T],
+    ) -> Result<(), &'static str> {
+        let frame_buf = scratch_buf
+            .get_mut(..out_buf.len())
+            .ok_or("Scratch buffer is too short.")?;
+        self.generate_frame(f_parms, frame_buf)?;
+        for (out, sample) in out_buf.iter_mut().zip(frame_buf.iter()) {
+            *out = converter.convert(*sample);
+        }
+        Ok(())
+    }
+
+    fn compute_next_output_signal_sample(&mut self) -> f64 {
+        let glottan_source: fn(&mut Generator<R>) -> f64 = self.glottal_source;
+        let mut voice = glottan_source(self);
+
+        let f_parms = self.f_parms.as_ref().unwrap();
+        let (cascade_enabled, parallel_enabled) =
+            (f_parms.cascade_enabled, f_parms.parallel_enabled);
+        let (tremolo_rate, tremolo_depth) = (f_parms.tremolo_rate, f_parms.tremolo_depth);
+        let p_state = self.p_state.as_ref().unwrap();
+
+        // apply spectral tilt
+        voice = self.vocal_tract.tilt_filter.step(voice);
+
+        // if within glottal open phase
+        if p_state.position_in_period < p_state.open_phase_length {
+            // add breathiness (turbulence)
+            voice += get_white_noise(&mut self.rng) * self.f_state.breathiness_lin;
+        }
+
+        let cascade_out = if cascade_enabled {
+            self.compute_cascade_branch(voice)
+        } else {
+            0.0
+        };
+
+        let parallel_out = if parallel_enabled {
+            self.compute_parallel_branch(voice)
+        } else {
+            0.0
+        };
+
+        let mut out = cascade_out + parallel_out;
+        out = self.vocal_tract.output_lp_filter.step(out);
+        out *= self.f_state.gain_lin;
+        if tremolo_depth > 0.0 {
+            let time = self.abs_position as f64 / self.m_parms.sample_rate as f64;
+            out *= get_tremolo_gain(tremolo_rate, tremolo_depth, time);
+        }
+        out
+    }
+
+    fn compute_cascade_branch(&mut self, voice: f64) -> f64 {
+        let f_parms = self.f_parms.as_ref().unwrap();
+        let p_state = self.p_state.as_ref().unwrap();
+        let cascade_voice = voice * self.f_state.cascade_voicing_lin;
+
+        let current_aspiration_mod = if p_state.is_noise_modulated() {
+            f_parms.cascade_aspiration_mod
+        } else {
+            0.0
+        };
+
+        let aspiration = self.aspiration_source_casc.get_next()
+            * self.f_state.cascade_aspiration_lin
+            * (1.0 - current_aspiration_mod);
+        self.vocal_tract.step_cascade(cascade_voice + aspiration)
+    }
+
+    fn compute_parallel_branch(&mut self, voice: f64) -> f64 {
+        let f_parms = self.f_parms.as_ref().unwrap();
+        let p_state = self.p_state.as_ref().unwrap();
+        let parallel_voice = voice * self.f_state.parallel_voicing_lin;
+
+        let current_aspiration_mod = if p_state.is_noise_modulated() {
+            f_parms.parallel_aspiration_mod
+        } else {
+            0.0
+        };
+
+        let aspiration = self.aspiration_source_par.get_next()
+            * self.f_state.parallel_aspiration_lin
+            * (1.0 - current_aspiration_mod);
+        let source = parallel_voice + aspiration;
+        let current_frication_mod = if p_state.is_noise_modulated() {
+            f_parms.frication_mod
+        } else {
+            0.0
+        };
+
+        let frication_noise = self.frication_source_par.get_next()
+            * self.f_state.frication_lin
+            * (1.0 - current_frication_mod);
+        self.vocal_tract
+            .step_parallel(source, frication_noise, self.f_state.parallel_bypass_lin)
+    }
+
+    /// Returns `true` if a new F0 period has to be started before the next sample.
+    fn needs_new_period(&self) -> bool {
+        let Some(p_state) = &self.p_state else {
+            return true;
+        };
+        if p_state.voiced {
+            return p_state.position_in_period >= p_state.period_length;
+        }
+        // While unvoiced there are no F0 periods to end.
+        // Voicing resumes as soon as new frame parameters are pending or the active f0 is positive again.
+        self.new_f_parms.is_some() || (self.f_parms.is_some() && self.get_frame_f0() > 0.0)
+    }
+
+    /// Returns the unmodulated f0 of the active frame at the current sample position.
+    fn get_frame_f0(&self) -> f64 {
+        let f_parms = self.f_parms.as_ref().unwrap();
+        let Some(f0_contour) = &f_parms.f0_contour else {
+            return f_parms.f0;
+        };
+        let sample_rate = self.m_parms.sample_rate as f64;
+        // With period-synchronous switching, the frame may be activated late or still be active
+        // after its nominal end; the contour is still evaluated relative to the nominal frame start.
+        let time = self.abs_position.saturating_sub(self.frame_timing.start) as f64 / sample_rate;
+        let duration = self.frame_timing.length as f64 / sample_rate;
+        f0_contour.value_at(time, duration).unwrap_or(f_parms.f0)
+    }
 
     /// Starts a new F0 period.
-    // this is fine because it only operates on two variables:
-    //
-    // - period_length
-    // - sample_rate
-    //
-    // Both of which will do.... something weird if it ends up being negative.
-    #[allow(clippy::cast_sign_loss)]
+    /// If the modulated f0 is not positive, the generator enters the unvoiced state instead.
     fn start_new_period(&mut self) -> Result<(), &'static str> {
-        if let Some(new_f_parms) = self.new_f_parms {
+        if let Some(new_f_parms) = self.new_f_parms.take() {
             // To reduce glitches, new frame parameters are only activated at the start of a new F0 period.
             self.f_parms = Some(new_f_parms);
-            self.new_f_parms = None;
+            self.frame_timing = self.new_frame_timing;
             self.start_using_new_frame_parameters()?;
         }
         if self.p_state.is_none() {
             self.p_state = Some(PeriodState::new());
         }
+        let frame_f0 = self.get_frame_f0();
+        if self.f_parms.as_ref().unwrap().drift_level > 0.0 {
+            // Each F0 period moves the random walk by up to a tenth of its range.
+            self.drift = (self.drift + 0.1 * get_white_noise(&mut self.rng)).clamp(-1.0, 1.0);
+        }
+        let f_parms = self.f_parms.as_ref().unwrap();
         let p_state = self.p_state.as_mut().unwrap();
-        let f_parms = self.f_parms.unwrap();
-        let flutter_time = self.abs_position / self.m_parms.sample_rate + self.flutter_time_offset;
-        p_state.f0 =
-            perform_frequency_modulation(f_parms.f0, f_parms.flutter_level, flutter_time as f64);
-
-        p_state.period_length = if p_state.f0 > 0.0 {
-            round((self.m_parms.sample_rate as f64) / p_state.f0) as usize
-        } else {
-            1
-        };
-
-        p_state.open_phase_length = if p_state.period_length > 1 {
-            round((p_state.period_length as f64) * f_parms.open_phase_ratio) as usize
-        } else {
-            0
-        };
+        let time = self.abs_position as f64 / self.m_parms.sample_rate as f64;
+        let flutter_time = time + self.flutter_time_offset as f64;
+        p_state.f0 = perform_frequency_modulation(frame_f0, f_parms.flutter_level, flutter_time);
+        p_state.f0 = perform_pitch_modulation(
+            p_state.f0,
+            f_parms.vibrato_rate,
+            f_parms.vibrato_depth,
+            self.drift * f_parms.drift_level,
+            time,
+        );
+
+        p_state.voiced = p_state.f0 > 0.0;
+        (p_state.period_length, p_state.open_phase_length) =
+            get_period_lengths(self.m_parms, p_state.f0, f_parms.open_phase_ratio);
 
         p_state.position_in_period = 0;
         self.start_glottal_source_period()?;
         Ok(())
     }
 
-    fn start_using_new_frame_parameters(&mut self) -> Result<(), &'static str> {
-        let f_parms = self.f_parms.unwrap();
-        self.f_state.breathiness_lin = db_to_lin(f_parms.breathiness_db);
-        self.f_state.gain_lin = db_to_lin(f_parms.gain_db);
-        let db = if f_parms.gain_db.is_finite() {
-            f_parms.gain_db
-        } else {
-            0.0
+    /// Activates the pending frame parameters immediately, instead of at the start of the next F0 period.
+    fn switch_frame_parameters(&mut self, crossfade_length: usize) -> Result<(), &'static str> {
+        let Some(new_f_parms) = self.new_f_parms.take() else {
+            return Ok(());
         };
-        self.f_state.gain_lin = db_to_lin(db);
-        set_tilt_filter(&mut self.tilt_filter, f_parms.tilt_db)?;
+        self.frame_timing = self.new_frame_timing;
+        if self.f_parms.is_none() || crossfade_length == 0 {
+            self.crossfade = None;
+            self.f_parms = Some(new_f_parms);
+            return self.start_using_new_frame_parameters();
+        }
+        let from = self.get_frame_snapshot();
+        let to = FrameSnapshot::new(self.m_parms, &new_f_parms)?;
+        self.f_parms = Some(new_f_parms);
+        self.crossfade = Some(Crossfade {
+            from,
+            to,
+            position: 0,
+            length: crossfade_length,
+        });
+        Ok(())
+    }
 
-        // Adjust cascade branch:
-        self.f_state.cascade_voicing_lin = db_to_lin(f_parms.cascade_voicing_db);
-        self.f_state.cascade_aspiration_lin = db_to_lin(f_parms.cascade_aspiration_db);
-        set_nasal_formant_casc(&mut self.nasal_formant_casc, f_parms)?;
-        set_nasal_antiformant_casc(&mut self.nasal_antiformant_casc, f_parms)?;
+    /// Advances a running crossfade by one sample.
+    fn step_crossfade(&mut self) {
+        let Some(crossfade) = self.crossfade.as_mut() else {
+            return;
+        };
+        crossfade.position += 1;
+        if crossfade.position >= crossfade.length {
+            let to = crossfade.to;
+            self.crossfade = None;
+            self.set_frame_snapshot(&to);
+            return;
+        }
+        let t = crossfade.position as f64 / crossfade.length as f64;
+        let snapshot = crossfade.from.interpolate(&crossfade.to, t);
+        self.set_frame_snapshot(&snapshot);
+    }
+
+    fn get_frame_snapshot(&self) -> FrameSnapshot {
+        let mut snapshot = FrameSnapshot {
+            f_state: self.f_state,
+            tilt_filter: self.vocal_tract.tilt_filter.get_coefficients(),
+            nasal_formant_casc: self.vocal_tract.nasal_formant_casc.get_coefficients(),
+            nasal_antiformant_casc: self.vocal_tract.nasal_antiformant_casc.get_coefficients(),
+            oral_formant_casc: [self.vocal_tract.nasal_formant_casc.get_coefficients();
+                MAX_ORAL_FORMANTS],
+            nasal_formant_par: self.vocal_tract.nasal_formant_par.get_coefficients(),
+            oral_formant_par: [self.vocal_tract.nasal_formant_par.get_coefficients();
+                MAX_ORAL_FORMANTS],
+        };
         for i in 0..MAX_ORAL_FORMANTS {
-            set_oral_formant_casc(&mut self.oral_formant_casc[i], f_parms, i)?;
+            snapshot.oral_formant_casc[i] =
+                self.vocal_tract.oral_formant_casc[i].get_coefficients();
+            snapshot.oral_formant_par[i] = self.vocal_tract.oral_formant_par[i].get_coefficients();
         }
+        snapshot
+    }
 
-        // Adjust parallel branch:
-        self.f_state.parallel_voicing_lin = db_to_lin(f_parms.parallel_voicing_db);
-        self.f_state.parallel_aspiration_lin = db_to_lin(f_parms.parallel_aspiration_db);
-        self.f_state.frication_lin = db_to_lin(f_parms.frication_db);
-        self.f_state.parallel_bypass_lin = db_to_lin(f_parms.parallel_bypass_db);
-        set_nasal_formant_par(&mut self.nasal_formant_par, f_parms)?;
+    fn set_frame_snapshot(&mut self, snapshot: &FrameSnapshot) {
+        self.f_state = snapshot.f_state;
+        self.vocal_tract
+            .tilt_filter
+            .set_coefficients(&snapshot.tilt_filter);
+        self.vocal_tract
+            .nasal_formant_casc
+            .set_coefficients(&snapshot.nasal_formant_casc);
+        self.vocal_tract
+            .nasal_antiformant_casc
+            .set_coefficients(&snapshot.nasal_antiformant_casc);
+        self.vocal_tract
+            .nasal_formant_par
+            .set_coefficients(&snapshot.nasal_formant_par);
         for i in 0..MAX_ORAL_FORMANTS {
-            set_oral_formant_par(&mut self.oral_formant_par[i], self.m_parms, f_parms, i)?;
+            self.vocal_tract.oral_formant_casc[i].set_coefficients(&snapshot.oral_formant_casc[i]);
+            self.vocal_tract.oral_formant_par[i].set_coefficients(&snapshot.oral_formant_par[i]);
         }
-        Ok(())
+    }
+
+    fn start_using_new_frame_parameters(&mut self) -> Result<(), &'static str> {
+        let f_parms = self.f_parms.as_ref().unwrap();
+        self.f_state = FrameState::from_frame_parms(f_parms);
+        self.vocal_tract.set_frame_parameters(self.m_parms, f_parms)
     }
 
     fn init_glottal_source(&mut self) {
@@ -1325,7 +2031,8 @@ fn set_oral_formant_par(
     // We are not doing this here, because then the output of the parallel branch would no longer
     // match the specified formant levels. Instead, we use the specified dB value to set the peak gain
     // instead of taking it as the DC gain.
-    if f.is_finite() && bw.is_finite() && peak_gain.is_finite() {
+    // A level of -99 dB or NaN results in a peak gain of 0, which mutes the formant.
+    if f.is_finite() && bw.is_finite() && peak_gain.is_finite() && peak_gain > 0.0 {
         oral_formant_par.set(f, bw, None)?;
         let w = 2.0 * PI * f / (m_parms.sample_rate as f64);
         let diff_gain = sqrt(2.0 - 2.0 * cos(w)); // gain of differencing filter
@@ -1376,29 +2083,224 @@ pub fn generate_sound<R: Rng + Clone>(
     f_parms_a: &Vec<FrameParms>,
     rng: R,
 ) -> Result<Vec<f64>, &'static str> {
-    let mut generator = Generator::new(m_parms, rng)?;
-    let mut out_buf_len = 0;
     for f_parms in f_parms_a {
-        out_buf_len += f_parms.duration * m_parms.sample_rate;
+        check_frame_duration(f_parms.duration)?;
     }
-    let mut out_buf: Vec<f64> = vec![0.0; out_buf_len];
+    let mut generator = Generator::new(m_parms, rng)?;
+    let duration: f64 = f_parms_a.iter().map(|f_parms| f_parms.duration).sum();
+    let mut out_buf: Vec<f64> =
+        vec![0.0; get_sample_position(m_parms.sample_rate as f64, duration)];
+    generate_frames(&mut generator, f_parms_a, &mut out_buf)?;
+    Ok(out_buf)
+}
 
+/// Generates consecutive frames into a buffer, which should hold their total duration.
+/// Frame boundaries are rounded from the accumulated time, so rounding errors do not add up.
+pub(crate) fn generate_frames<R: Rng + Clone>(
+    generator: &mut Generator<'_, R>,
+    f_parms_a: impl IntoIterator<Item = impl Borrow<FrameParms>>,
+    out_buf: &mut [f64],
+) -> Result<(), &'static str> {
+    let mut time = 0.0;
     let mut out_buf_pos = 0;
     for f_parms in f_parms_a {
-        let frame_len = f_parms.duration * m_parms.sample_rate;
-        let frame_buf = &mut out_buf[out_buf_pos..(out_buf_pos + frame_len)];
-        generator.generate_frame(f_parms, frame_buf)?;
-        out_buf_pos += frame_len;
+        let f_parms = f_parms.borrow();
+        check_frame_duration(f_parms.duration)?;
+        time += f_parms.duration;
+        let frame_end =
+            get_sample_position(generator.m_parms.sample_rate as f64, time).min(out_buf.len());
+        generator.generate_frame(f_parms, &mut out_buf[out_buf_pos..frame_end])?;
+        out_buf_pos = frame_end;
+    }
+    Ok(())
+}
+
+/// Frames must not end before they start.
+fn check_frame_duration(duration: f64) -> Result<(), &'static str> {
+    if !duration.is_finite() || duration < 0.0 {
+        return Err("Invalid frame duration.");
+    }
+    Ok(())
+}
+
+/// Returns the impulse response of the vocal tract for a frame.
+///
+/// The input is fed through the same filters that are used by the `Generator`, including the
+/// voicing levels and the overall gain, but without the glottal source, the noise sources, tremolo
+/// and automatic gain control. The result corresponds to `get_vocal_tract_transfer_function_coefficients`.
+/// Unstable filter settings show up as growing or non-finite samples.
+///
+/// ### params
+/// ```text
+///    n = Number of samples to compute.
+/// ```
+///
+/// # Errors
+///
+/// Any invalid parameters will return a static str explaining the invalid param.
+pub fn impulse_response(
+    m_parms: &MainParms,
+    f_parms: &FrameParms,
+    n: usize,
+) -> Result<Vec<f64>, &'static str> {
+    let input = (0..n).map(|i| if i == 0 { 1.0 } else { 0.0 });
+    get_vocal_tract_response(m_parms, f_parms, input)
+}
+
+/// Returns the step response of the vocal tract for a frame.
+/// See `impulse_response` for the parts of the synthesizer that are included.
+///
+/// ### params
+/// ```text
+///    n = Number of samples to compute.
+/// ```
+///
+/// # Errors
+///
+/// Any invalid parameters will return a static str explaining the invalid param.
+pub fn step_response(
+    m_parms: &MainParms,
+    f_parms: &FrameParms,
+    n: usize,
+) -> Result<Vec<f64>, &'static str> {
+    get_vocal_tract_response(m_parms, f_parms, (0..n).map(|_| 1.0))
+}
+
+/// Feeds an input signal through the vocal tract, in the same way as `Generator` does with the voice signal.
+fn get_vocal_tract_response(
+    m_parms: &MainParms,
+    f_parms: &FrameParms,
+    input: impl Iterator<Item = f64>,
+) -> Result<Vec<f64>, &'static str> {
+    let mut vocal_tract = VocalTract::new(m_parms)?;
+    vocal_tract.set_frame_parameters(m_parms, f_parms)?;
+    let f_state = FrameState::from_frame_parms(f_parms);
+    Ok(input
+        .map(|x| {
+            let voice = vocal_tract.tilt_filter.step(x);
+            let cascade_out = if f_parms.cascade_enabled {
+                vocal_tract.step_cascade(voice * f_state.cascade_voicing_lin)
+            } else {
+                0.0
+            };
+            let parallel_out = if f_parms.parallel_enabled {
+                vocal_tract.step_parallel(
+                    voice * f_state.parallel_voicing_lin,
+                    0.0,
+                    f_state.parallel_bypass_lin,
+                )
+            } else {
+                0.0
+            };
+            vocal_tract
+                .output_lp_filter
+                .step(cascade_out + parallel_out)
+                * f_state.gain_lin
+        })
+        .collect())
+}
+
+/// Estimates the glottal source signal of a sound by inverse filtering.
+///
+/// The sound is filtered with the inverse of the cascade branch of the vocal tract: an
+/// anti-resonator for each resonator and a resonator for the nasal antiformant, and the inverse
+/// of the output low-pass filter. The overall gain and the cascade voicing level are divided out,
+/// unless they are muted.
+/// For a sound generated by the cascade branch, the result is the glottal source after the
+/// spectral tilt filter, i.e. the glottal flow derivative, plus the breathiness and aspiration noise.
+///
+/// The frames are applied at the same sample positions as in `generate_sound`. Samples after the
+/// end of the last frame are filtered with the last frame. The parallel branch is not inverted,
+/// so it should not contribute to the sound. With AGC, the gain is not known and assumed to be 0 dB.
+/// An antiformant with a negative bandwidth results in an unstable inverse filter.
+///
+/// ### params
+///
+/// ```text
+/// samples:   The sound.
+/// f_parms_a: The frame parameters that match the sound, e.g. from `analyze`.
+/// ```
+///
+/// # Errors
+///
+/// Any invalid parameters will return a static str explaining the invalid param.
+pub fn inverse_filter(
+    m_parms: &MainParms,
+    f_parms_a: &[FrameParms],
+    samples: &[f64],
+) -> Result<Vec<f64>, &'static str> {
+    if f_parms_a.is_empty() {
+        return Err("No frame parameters.");
+    }
+    let mut vocal_tract = VocalTract::new(m_parms)?;
+    let mut inverse = InverseVocalTract::new(m_parms);
+    let mut out_buf = Vec::with_capacity(samples.len());
+    let mut time = 0.0;
+    for (i, f_parms) in f_parms_a.iter().enumerate() {
+        time += f_parms.duration;
+        let frame_end = if i + 1 == f_parms_a.len() {
+            samples.len()
+        } else {
+            get_sample_position(m_parms.sample_rate as f64, time)
+                .clamp(out_buf.len(), samples.len())
+        };
+        vocal_tract.set_frame_parameters(m_parms, f_parms)?;
+        inverse.set_frame_parameters(&vocal_tract);
+        let f_state = FrameState::from_frame_parms(f_parms);
+        // Muted levels cannot be divided out. The gain is applied after the filters,
+        // the voicing level before them, so a change of the levels does not cause a transient.
+        let gain_lin = positive_or_one(f_state.gain_lin);
+        let voicing_lin = positive_or_one(f_state.cascade_voicing_lin);
+        for x in &samples[out_buf.len()..frame_end] {
+            out_buf.push(inverse.step(*x / gain_lin) / voicing_lin);
+        }
     }
     Ok(out_buf)
 }
 
+fn positive_or_one(x: f64) -> f64 {
+    if x > 0.0 {
+        x
+    } else {
+        1.0
+    }
+}
+
+/// Returns the position of a point in time, in seconds, rounded to the nearest sample of the
+/// specified rate. Negative times and NaN are mapped to 0.
+#[allow(clippy::cast_sign_loss)]
+pub(crate) fn get_sample_position(sample_rate: f64, time: f64) -> usize {
+    round(time * sample_rate).max(0.0) as usize
+}
+
+/// Returns the F0 period length and the open glottis phase length, both in samples.
+/// Both are 0 if `f0` is not positive.
+// this is fine because it only operates on two variables:
+//
+// - period_length
+// - sample_rate
+//
+// Both of which will do.... something weird if it ends up being negative.
+#[allow(clippy::cast_sign_loss)]
+fn get_period_lengths(m_parms: &MainParms, f0: f64, open_phase_ratio: f64) -> (usize, usize) {
+    let period_length = if f0 > 0.0 {
+        round((m_parms.sample_rate as f64) / f0) as usize
+    } else {
+        0
+    };
+    let open_phase_length = if period_length > 1 {
+        round((period_length as f64) * open_phase_ratio) as usize
+    } else {
+        0
+    };
+    (period_length, open_phase_length)
+}
+
 //--- Transfer function --------------------------------------------------------
 
 const EPS: f64 = 1E-10;
 
-/// Returns the polynomial coefficients of the overall filter transfer function in the z-plane.
-/// The returned array contains the top and bottom coefficients of the rational fraction, ordered in ascending powers.
+/// Returns the overall filter transfer function in the z-plane, as a rational function in `z^-1`.
 ///
 /// # Errors
 ///
@@ -1406,32 +2308,30 @@ const EPS: f64 = 1E-10;
 pub fn get_vocal_tract_transfer_function_coefficients(
     m_parms: &MainParms,
     f_parms: &FrameParms,
-) -> Result<Vec<Vec<f64>>, &'static str> {
+) -> Result<RationalFunction, &'static str> {
     // glottal source
-    let mut voice: Vec<Vec<f64>> = vec![vec![1.0], vec![1.0]];
+    let mut voice = RationalFunction::constant(1.0).with_epsilon(EPS);
     //
     let mut tilt_filter = LpFilter1::new(m_parms.sample_rate);
     set_tilt_filter(&mut tilt_filter, f_parms.tilt_db)?;
-    let tilt_trans = &tilt_filter.get_transfer_function_coefficients();
-    voice = poly_real::multiply_fractions(&voice, tilt_trans, Some(EPS))?;
+    voice = &voice * &tilt_filter.get_transfer_function_coefficients();
     //
     let cascade_trans = if f_parms.cascade_enabled {
         get_cascade_branch_transfer_function_coefficients(m_parms, f_parms)?
     } else {
-        vec![vec![0.0], vec![1.0]]
+        RationalFunction::constant(0.0)
     };
     let parallel_trans = if f_parms.parallel_enabled {
         get_parallel_branch_transfer_function_coefficients(m_parms, f_parms)?
     } else {
-        vec![vec![0.0], vec![1.0]]
+        RationalFunction::constant(0.0)
     };
-    let branches_trans = poly_real::add_fractions(&cascade_trans, &parallel_trans, Some(EPS))?;
-    let mut out = poly_real::multiply_fractions(&voice, &branches_trans, Some(EPS))?;
+    let branches_trans = &cascade_trans + &parallel_trans;
+    let mut out = &voice * &branches_trans;
     //
     let mut output_lp_filter = Resonator::new(m_parms.sample_rate);
     output_lp_filter.set(0.0, m_parms.sample_rate as f64 / 2.0, None)?;
-    let output_lp_trans = output_lp_filter.get_transfer_function_coefficients();
-    out = poly_real::multiply_fractions(&out, &output_lp_trans, Some(EPS))?;
+    out = &out * &output_lp_filter.get_transfer_function_coefficients();
     //
     let db = if f_parms.gain_db.is_finite() {
         f_parms.gain_db
@@ -1439,33 +2339,215 @@ pub fn get_vocal_tract_transfer_function_coefficients(
         0.0
     };
     let gain_lin = db_to_lin(db);
-    out = poly_real::multiply_fractions(&out, &[vec![gain_lin], vec![1.0]], Some(EPS))?;
+    out = &out * &RationalFunction::constant(gain_lin);
     //
     Ok(out)
 }
 
+/// Returns the transfer function of the whole synthesizer, including the glottal source,
+/// in the z-plane, as a rational function in `z^-1`.
+///
+/// This is the product of the transfer function of the glottal source model for a single F0 period
+/// and the vocal tract transfer function returned by `get_vocal_tract_transfer_function_coefficients`.
+/// The radiation characteristic is part of the glottal source models, which generate the derivative
+/// of the glottal flow. At the harmonics of F0, the magnitude of the result is the amplitude of the
+/// generated harmonics, multiplied by the period length.
+///
+/// The period is computed from the f0 at the start of the frame and `open_phase_ratio`.
+/// Flutter, vibrato, drift, tremolo, breathiness and aspiration are not included.
+/// For the noise source, a flat spectrum with the amplitude of the noise samples is assumed.
+///
+/// # Errors
+///
+/// Any invalid parameters will return a static str explaining the invalid param.
+pub fn get_system_transfer_function_coefficients(
+    m_parms: &MainParms,
+    f_parms: &FrameParms,
+) -> Result<RationalFunction, &'static str> {
+    let source = get_glottal_source_transfer_function_coefficients(m_parms, f_parms)?;
+    let vocal_tract = get_vocal_tract_transfer_function_coefficients(m_parms, f_parms)?;
+    Ok(&vocal_tract * &source)
+}
+
+/// Returns the transfer function of the glottal source model for one F0 period,
+/// i.e. the z-transform of the source signal of a single period.
+pub(crate) fn get_glottal_source_transfer_function_coefficients(
+    m_parms: &MainParms,
+    f_parms: &FrameParms,
+) -> Result<RationalFunction, &'static str> {
+    let f0 = f_parms
+        .f0_contour
+        .as_ref()
+        .and_then(|f0_contour| f0_contour.value_at(0.0, f_parms.duration))
+        .unwrap_or(f_parms.f0);
+    let (_, open_phase_length) = get_period_lengths(m_parms, f0, f_parms.open_phase_ratio);
+    match m_parms.glottal_source_type {
+        GlottalSourceType::Impulsive => {
+            if open_phase_length == 0 {
+                return Ok(RationalFunction::constant(0.0));
+            }
+            // same as `ImpulsiveGlottalSource`: a doublet at positions 1 and 2, shaped by a resonator
+            let mut source = ImpulsiveGlottalSource::new(m_parms.sample_rate);
+            source.start_period(open_phase_length)?;
+            let resonator_trans = source
+                .resonator
+                .as_ref()
+                .unwrap()
+                .get_transfer_function_coefficients();
+            let doublet = RationalFunction::new(
+                Polynomial::new(vec![0.0, 1.0, -1.0]),
+                Polynomial::constant(1.0),
+            );
+            Ok(&doublet * &resonator_trans)
+        }
+        GlottalSourceType::Natural => {
+            // the pulse of a single period, as an FIR filter
+            let mut source = NaturalGlottalSource::new();
+            source.start_period(open_phase_length);
+            let pulse = (0..open_phase_length).map(|_| source.get_next()).collect();
+            Ok(RationalFunction::new(
+                Polynomial::new(pulse),
+                Polynomial::constant(1.0),
+            ))
+        }
+        GlottalSourceType::Noise => Ok(RationalFunction::constant(1.0)),
+    }
+}
+
+/// Returns the filters of the vocal tract as second-order sections,
+/// built from the coefficients of the filters that are used for the synthesis.
+/// Passthrough filters are omitted.
+///
+/// The combined transfer function of the sections is the same as the result of
+/// `get_vocal_tract_transfer_function_coefficients`,
+/// but the sections avoid the rounding errors of the expanded high order polynomials.
+///
+/// # Errors
+///
+/// Any invalid parameters will return a static str explaining the invalid param.
+pub fn get_vocal_tract_second_order_sections(
+    m_parms: &MainParms,
+    f_parms: &FrameParms,
+) -> Result<VocalTractSections, &'static str> {
+    let passthrough = SecondOrderSection::gain(1.0);
+    let mut sections = VocalTractSections::default();
+    //
+    let mut tilt_filter = LpFilter1::new(m_parms.sample_rate);
+    set_tilt_filter(&mut tilt_filter, f_parms.tilt_db)?;
+    sections.source.push(tilt_filter.get_second_order_section());
+    sections.source.retain(|s| *s != passthrough);
+    //
+    sections.cascade = if f_parms.cascade_enabled {
+        get_cascade_branch_second_order_sections(m_parms, f_parms)?
+    } else {
+        vec![SecondOrderSection::gain(0.0)]
+    };
+    if f_parms.parallel_enabled {
+        sections.parallel = get_parallel_branch_second_order_sections(m_parms, f_parms)?;
+    }
+    //
+    let mut output_lp_filter = Resonator::new(m_parms.sample_rate);
+    output_lp_filter.set(0.0, m_parms.sample_rate as f64 / 2.0, None)?;
+    let db = if f_parms.gain_db.is_finite() {
+        f_parms.gain_db
+    } else {
+        0.0
+    };
+    sections.output.push(
+        output_lp_filter
+            .get_second_order_section()
+            .scale(db_to_lin(db)),
+    );
+    Ok(sections)
+}
+
+fn get_cascade_branch_second_order_sections(
+    m_parms: &MainParms,
+    f_parms: &FrameParms,
+) -> Result<Vec<SecondOrderSection>, &'static str> {
+    let mut sections = Vec::new();
+    //
+    let mut nasal_antiformant_casc = AntiResonator::new(m_parms.sample_rate);
+    set_nasal_antiformant_casc(&mut nasal_antiformant_casc, f_parms)?;
+    sections.push(nasal_antiformant_casc.get_second_order_section());
+    //
+    let mut nasal_formant_casc = Resonator::new(m_parms.sample_rate);
+    set_nasal_formant_casc(&mut nasal_formant_casc, f_parms)?;
+    sections.push(nasal_formant_casc.get_second_order_section());
+    //
+    for i in 0..MAX_ORAL_FORMANTS {
+        let mut oral_formant_casc = Resonator::new(m_parms.sample_rate);
+        set_oral_formant_casc(&mut oral_formant_casc, f_parms, i)?;
+        sections.push(oral_formant_casc.get_second_order_section());
+    }
+    //
+    sections.retain(|s| *s != SecondOrderSection::gain(1.0));
+    // the voicing level is included in the first section
+    let cascade_voicing_lin = db_to_lin(f_parms.cascade_voicing_db);
+    match sections.first_mut() {
+        Some(first) => *first = first.scale(cascade_voicing_lin),
+        None => sections.push(SecondOrderSection::gain(cascade_voicing_lin)),
+    }
+    Ok(sections)
+}
+
+fn get_parallel_branch_second_order_sections(
+    m_parms: &MainParms,
+    f_parms: &FrameParms,
+) -> Result<Vec<SecondOrderSection>, &'static str> {
+    let parallel_voicing_lin = db_to_lin(f_parms.parallel_voicing_db);
+    // Applies the differencing filter `1 - z^-1` to a section without zeros.
+    let difference = |s: SecondOrderSection| SecondOrderSection { b1: -s.b0, ..s };
+    let mut sections = Vec::new();
+    //
+    let mut nasal_formant_par = Resonator::new(m_parms.sample_rate);
+    set_nasal_formant_par(&mut nasal_formant_par, f_parms)?;
+    sections.push(
+        nasal_formant_par
+            .get_second_order_section()
+            .scale(parallel_voicing_lin),
+    );
+    //
+    for i in 0..MAX_ORAL_FORMANTS {
+        let mut oral_formant_par = Resonator::new(m_parms.sample_rate);
+        set_oral_formant_par(&mut oral_formant_par, m_parms, f_parms, i)?;
+        let alternating_sign = if i % 2 == 0 { 1.0 } else { -1.0 };
+        let section = oral_formant_par
+            .get_second_order_section()
+            .scale(parallel_voicing_lin * alternating_sign);
+        // F1 is applied to source, F2 to F6 are applied to difference
+        sections.push(if i == 0 { section } else { difference(section) });
+    }
+    //
+    // bypass is applied to source difference
+    let parallel_bypass_lin = db_to_lin(f_parms.parallel_bypass_db);
+    sections.push(difference(SecondOrderSection::gain(
+        parallel_voicing_lin * parallel_bypass_lin,
+    )));
+    //
+    sections.retain(|s| !s.is_zero());
+    Ok(sections)
+}
+
 fn get_cascade_branch_transfer_function_coefficients(
     m_parms: &MainParms,
     f_parms: &FrameParms,
-) -> Result<Vec<Vec<f64>>, &'static str> {
+) -> Result<RationalFunction, &'static str> {
     let cascade_voicing_lin = db_to_lin(f_parms.cascade_voicing_db);
-    let mut v: Vec<Vec<f64>> = vec![vec![cascade_voicing_lin], vec![1.0]];
+    let mut v = RationalFunction::constant(cascade_voicing_lin).with_epsilon(EPS);
     //
     let mut nasal_antiformant_casc = AntiResonator::new(m_parms.sample_rate);
     set_nasal_antiformant_casc(&mut nasal_antiformant_casc, f_parms)?;
-    let nasal_antiformant_trans = nasal_antiformant_casc.get_transfer_function_coefficients();
-    v = poly_real::multiply_fractions(&v, &nasal_antiformant_trans, Some(EPS))?;
+    v = &v * &nasal_antiformant_casc.get_transfer_function_coefficients();
     //
     let mut nasal_formant_casc = Resonator::new(m_parms.sample_rate);
     set_nasal_formant_casc(&mut nasal_formant_casc, f_parms)?;
-    let nasal_formant_trans = nasal_formant_casc.get_transfer_function_coefficients();
-    v = poly_real::multiply_fractions(&v, &nasal_formant_trans, Some(EPS))?;
+    v = &v * &nasal_formant_casc.get_transfer_function_coefficients();
     //
     for i in 0..MAX_ORAL_FORMANTS {
         let mut oral_formant_casc = Resonator::new(m_parms.sample_rate);
         set_oral_formant_casc(&mut oral_formant_casc, f_parms, i)?;
-        let oral_formant_casc_trans = oral_formant_casc.get_transfer_function_coefficients();
-        v = poly_real::multiply_fractions(&v, &oral_formant_casc_trans, Some(EPS))?;
+        v = &v * &oral_formant_casc.get_transfer_function_coefficients();
     }
     //
     Ok(v)
@@ -1474,50 +2556,30 @@ fn get_cascade_branch_transfer_function_coefficients(
 fn get_parallel_branch_transfer_function_coefficients(
     m_parms: &MainParms,
     f_parms: &FrameParms,
-) -> Result<Vec<Vec<f64>>, &'static str> {
+) -> Result<RationalFunction, &'static str> {
     let parallel_voicing_lin = db_to_lin(f_parms.parallel_voicing_db);
-    let source: Vec<Vec<f64>> = vec![vec![parallel_voicing_lin], vec![1.0]];
+    let source = RationalFunction::constant(parallel_voicing_lin).with_epsilon(EPS);
     //
     let differencing_filter = DifferencingFilter::new();
-    let differencing_filter_trans = differencing_filter.get_transfer_function_coefficients();
-    let source2 = poly_real::multiply_fractions(&source, &differencing_filter_trans, Some(EPS))?;
-    //
-    let mut v: Vec<Vec<f64>> = vec![vec![0.0], vec![1.0]];
+    let source2 = &source * &differencing_filter.get_transfer_function_coefficients();
     //
     let mut nasal_formant_par = Resonator::new(m_parms.sample_rate);
     set_nasal_formant_par(&mut nasal_formant_par, f_parms)?;
-    let nasal_formant_trans = nasal_formant_par.get_transfer_function_coefficients();
-    v = poly_real::add_fractions(
-        &v,
-        &poly_real::multiply_fractions(&source, &nasal_formant_trans, None)?,
-        Some(EPS),
-    )?;
+    let mut v = &source * &nasal_formant_par.get_transfer_function_coefficients();
     //
     for i in 0..MAX_ORAL_FORMANTS {
         let mut oral_formant_par = Resonator::new(m_parms.sample_rate);
         set_oral_formant_par(&mut oral_formant_par, m_parms, f_parms, i)?;
-        let oral_pformant_trans = oral_formant_par.get_transfer_function_coefficients();
         // F1 is applied to source, F2 to F6 are applied to difference
         let formant_in = if i == 0 { &source } else { &source2 };
-        let formant_out =
-            poly_real::multiply_fractions(formant_in, &oral_pformant_trans, Some(EPS))?;
+        let formant_out = formant_in * &oral_formant_par.get_transfer_function_coefficients();
         let alternating_sign = if i % 2 == 0 { 1.0 } else { -1.0 };
-        let v2 = poly_real::multiply_fractions(
-            &formant_out,
-            &[vec![alternating_sign], vec![1.0]],
-            Some(EPS),
-        )?;
-        v = poly_real::add_fractions(&v, &v2, Some(EPS))?;
+        v = &v + &(&formant_out * &RationalFunction::constant(alternating_sign));
     }
     //
     let parallel_bypass_lin = db_to_lin(f_parms.parallel_bypass_db);
     // bypass is applied to source difference
-    let parallel_bypass = poly_real::multiply_fractions(
-        &source2,
-        &[vec![parallel_bypass_lin], vec![1.0]],
-        Some(EPS),
-    )?;
-    v = poly_real::add_fractions(&v, &parallel_bypass, Some(EPS))?;
+    v = &v + &(&source2 * &RationalFunction::constant(parallel_bypass_lin));
     //
     Ok(v)
 }
@@ -28,12 +28,80 @@ compile_error!("Must specify a math feature: either \"std\" or \"libm\".");
 
 extern crate alloc;
 
+#[cfg(feature = "analysis")]
+mod analysis;
+#[cfg(feature = "analysis")]
+pub use analysis::{analyze, get_lpc_coefficients, AnalysisParms};
+#[cfg(feature = "analysis")]
+mod vocoder;
+#[cfg(feature = "analysis")]
+pub use vocoder::{scale_formants, shift_pitch, vocode, whisper};
+mod complex;
+pub use complex::Complex;
+mod frequency_response;
+pub use frequency_response::{
+    evaluate_frequency_response, get_signal_spectrum, get_system_frequency_response,
+    get_vocal_tract_frequency_response, FrequencyGrid, FrequencyResponsePoint,
+};
+mod spectral_peaks;
+mod spectrum;
+pub use spectral_peaks::{find_spectral_peaks, get_vocal_tract_spectral_peaks, SpectralPeak};
+pub use spectrum::{
+    fft, get_bin_frequencies, ifft, long_term_average_spectrum, power_spectrum, spectrogram,
+    SpectrumParms, Window,
+};
 mod traits;
 pub use traits::{BasicFilter, Filter};
 mod klatt;
 mod math;
+mod metrics;
 pub use klatt::{
-    generate_sound, get_vocal_tract_transfer_function_coefficients, FrameParms, GlottalSourceType,
-    MainParms,
+    generate_sound, get_system_transfer_function_coefficients,
+    get_vocal_tract_second_order_sections, get_vocal_tract_transfer_function_coefficients,
+    impulse_response, inverse_filter, step_response, F0Contour, FrameParms, FrameSwitchPolicy,
+    Generator, GlottalSourceType, MainParms,
+};
+pub use metrics::{
+    compare_renders, f0_rmse, log_spectral_distance, mel_cepstral_distortion, MetricsParms,
+    QualityMetrics,
+};
+mod optimizer;
+pub use optimizer::{
+    fit_frame_parameters, get_spectral_distance, OptimizationResult, OptimizerParms, SpectrumModel,
+};
+mod parallel_levels;
+pub use parallel_levels::match_parallel_formant_levels;
+mod pcm;
+pub use pcm::{
+    decode_alaw, decode_mulaw, encode_alaw, encode_mulaw, to_alaw, to_i16, to_i24, to_i32,
+    to_mulaw, ALaw, Dither, MuLaw, Quantizer, SampleConverter,
+};
+mod pitch;
+pub use pitch::{estimate_pitch, track_pitch, PitchEstimate, PitchParms};
+mod poles_zeros;
+pub use poles_zeros::{
+    get_poles_and_zeros, get_transfer_function_from_poles_and_zeros,
+    get_vocal_tract_poles_and_zeros, PolesAndZeros, Resonance,
+};
+mod poly_complex;
+pub use poly_complex::{
+    evaluate as evaluate_complex_polynomial, expand_roots, multiply as multiply_complex_polynomials,
 };
 mod poly_real;
+#[cfg(feature = "render")]
+mod render;
+#[cfg(feature = "render")]
+pub use render::{
+    plot_frequency_response, render_spectrogram, GrayImage, ResponsePlotParms,
+    SpectrogramImageParms,
+};
+mod polynomial;
+pub use polynomial::{Polynomial, RationalFunction};
+mod score;
+mod second_order_section;
+pub use score::{Parameter, ParameterTrack, Score, ScoreFrames};
+pub use second_order_section::{SecondOrderSection, VocalTractSections};
+#[cfg(feature = "wav")]
+mod wav;
+#[cfg(feature = "wav")]
+pub use wav::{decode_wav, encode_wav, WavSampleFormat, WavSink, WavSpec, WavWriter};
@@ -4,7 +4,7 @@
 //! the `libm` equiv. `sqrt(f)`.
 
 #[cfg(feature = "libm")]
-pub(crate) use libm::{cos, exp, pow, round, sin, sqrt};
+pub(crate) use libm::{atan2, cos, exp, log, log10, pow, round, sin, sqrt};
 
 #[cfg(feature = "std")]
 pub(crate) fn sqrt(f: f64) -> f64 {
@@ -20,7 +20,7 @@ pub(crate) fn cos(f: f64) -> f64 {
 }
 #[cfg(feature = "std")]
 pub(crate) fn sin(f: f64) -> f64 {
-    f.cos()
+    f.sin()
 }
 #[cfg(feature = "std")]
 pub(crate) fn exp(f: f64) -> f64 {
@@ -30,3 +30,15 @@ pub(crate) fn exp(f: f64) -> f64 {
 pub(crate) fn round(f: f64) -> f64 {
     f.round()
 }
+#[cfg(feature = "std")]
+pub(crate) fn atan2(y: f64, x: f64) -> f64 {
+    y.atan2(x)
+}
+#[cfg(feature = "std")]
+pub(crate) fn log10(f: f64) -> f64 {
+    f.log10()
+}
+#[cfg(feature = "std")]
+pub(crate) fn log(f: f64) -> f64 {
+    f.ln()
+}
@@ -0,0 +1,317 @@
+//! Objective quality metrics between two signals, e.g. a render and a reference render.
+//!
+//! Unlike a sample-by-sample comparison, these metrics tolerate small numeric drift (phase, noise,
+//! rounding), and only grow with audible differences of the spectral envelope or the pitch.
+
+use crate::math::{cos, log, log10, pow, sqrt};
+use crate::pitch::{estimate_pitch, PitchParms};
+use crate::spectrum::{get_bin_frequencies, get_power_spectra, SpectrumParms, Window};
+use alloc::{vec, vec::Vec};
+use core::f64::consts::{LN_10, PI};
+
+/// Power spectra of consecutive analysis windows.
+type Spectra = Vec<Vec<f64>>;
+
+/// Parameters for the quality metrics.
+#[derive(Clone, Debug)]
+pub struct MetricsParms {
+    /// parameters of the spectral analysis, the metrics are averaged over its analysis windows
+    pub spectrum: SpectrumParms,
+    /// power levels below this level in dB are raised to it, so silence does not dominate the metrics
+    pub floor_db: f64,
+    /// number of triangular mel filters for the mel-cepstral distortion
+    pub mel_bands: usize,
+    /// number of cepstral coefficients for the mel-cepstral distortion, excluding the energy `c0`
+    pub cepstral_order: usize,
+    /// parameters of the pitch estimation, at the centers of the analysis windows
+    pub pitch_parms: PitchParms,
+}
+
+impl Default for MetricsParms {
+    fn default() -> Self {
+        Self {
+            spectrum: SpectrumParms {
+                window: Window::Hann,
+                window_length: 1024,
+                hop_length: 256,
+                fft_size: 1024,
+            },
+            floor_db: -100.0,
+            mel_bands: 40,
+            cepstral_order: 13,
+            pitch_parms: PitchParms::default(),
+        }
+    }
+}
+
+/// The result of `compare_renders`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QualityMetrics {
+    /// log-spectral distance in dB, see `log_spectral_distance`
+    pub log_spectral_distance: f64,
+    /// mel-cepstral distortion in dB, see `mel_cepstral_distortion`
+    pub mel_cepstral_distortion: f64,
+    /// RMS f0 difference in Hz, see `f0_rmse`
+    pub f0_rmse: f64,
+    /// fraction of the analysis windows that are voiced in only one of the signals, 0 .. 1
+    pub voicing_error_rate: f64,
+    /// number of analysis windows that were compared
+    pub frame_count: usize,
+}
+
+/// Computes all quality metrics between a reference signal and a test signal.
+///
+/// Only the analysis windows that fit into both signals are compared, so the signals should be
+/// aligned at the start.
+///
+/// ### params
+///
+/// ```text
+/// reference:   The reference signal.
+/// test:        The signal that is compared with the reference.
+/// sample_rate: Sample rate of both signals in Hz.
+/// mt_parms:    The metrics parameters.
+/// ```
+///
+/// # Errors
+///
+/// Any invalid parameters will return a static str explaining the invalid param.
+pub fn compare_renders(
+    reference: &[f64],
+    test: &[f64],
+    sample_rate: usize,
+    mt_parms: &MetricsParms,
+) -> Result<QualityMetrics, &'static str> {
+    let (f0_rmse, voicing_error_rate) = get_f0_errors(reference, test, sample_rate, mt_parms)?;
+    let (reference_spectra, test_spectra) = get_spectra(reference, test, mt_parms)?;
+    Ok(QualityMetrics {
+        log_spectral_distance: get_log_spectral_distance(
+            &reference_spectra,
+            &test_spectra,
+            mt_parms,
+        ),
+        mel_cepstral_distortion: get_mel_cepstral_distortion(
+            &reference_spectra,
+            &test_spectra,
+            sample_rate,
+            mt_parms,
+        )?,
+        f0_rmse,
+        voicing_error_rate,
+        frame_count: reference_spectra.len(),
+    })
+}
+
+/// Returns the log-spectral distance between two signals in dB.
+///
+/// For each analysis window, this is the RMS difference of the power spectra in dB over all
+/// frequency bins. The result is the mean over all analysis windows.
+///
+/// # Errors
+///
+/// Any invalid parameters will return a static str explaining the invalid param.
+pub fn log_spectral_distance(
+    reference: &[f64],
+    test: &[f64],
+    mt_parms: &MetricsParms,
+) -> Result<f64, &'static str> {
+    let (reference_spectra, test_spectra) = get_spectra(reference, test, mt_parms)?;
+    Ok(get_log_spectral_distance(
+        &reference_spectra,
+        &test_spectra,
+        mt_parms,
+    ))
+}
+
+/// Returns the mel-cepstral distortion between two signals in dB.
+///
+/// For each analysis window, the power spectrum is reduced to `mel_bands` log energies of
+/// triangular filters, equally spaced on the mel scale from 0 Hz to half the sample rate, which are
+/// converted to cepstral coefficients with a DCT-II. The distortion of a window is
+/// `10 / ln(10) * sqrt(2 * sum((c1[k] - c2[k])^2))` over the coefficients `1 ..= cepstral_order`,
+/// so the overall level (`c0`) is ignored. The result is the mean over all analysis windows.
+///
+/// # Errors
+///
+/// Any invalid parameters will return a static str explaining the invalid param.
+pub fn mel_cepstral_distortion(
+    reference: &[f64],
+    test: &[f64],
+    sample_rate: usize,
+    mt_parms: &MetricsParms,
+) -> Result<f64, &'static str> {
+    let (reference_spectra, test_spectra) = get_spectra(reference, test, mt_parms)?;
+    get_mel_cepstral_distortion(&reference_spectra, &test_spectra, sample_rate, mt_parms)
+}
+
+/// Returns the RMS difference in Hz of the fundamental frequency of two signals.
+///
+/// The pitch is estimated at the centers of the analysis windows, and only windows that are voiced
+/// in both signals are compared. Returns NaN if there are no such windows.
+///
+/// # Errors
+///
+/// Any invalid parameters will return a static str explaining the invalid param.
+pub fn f0_rmse(
+    reference: &[f64],
+    test: &[f64],
+    sample_rate: usize,
+    mt_parms: &MetricsParms,
+) -> Result<f64, &'static str> {
+    Ok(get_f0_errors(reference, test, sample_rate, mt_parms)?.0)
+}
+
+/// Returns the power spectra of the analysis windows that fit into both signals.
+fn get_spectra(
+    reference: &[f64],
+    test: &[f64],
+    mt_parms: &MetricsParms,
+) -> Result<(Spectra, Spectra), &'static str> {
+    if mt_parms.floor_db.is_nan() {
+        return Err("Invalid floor level.");
+    }
+    let length = reference.len().min(test.len());
+    let reference_spectra = get_power_spectra(&reference[..length], &mt_parms.spectrum)?;
+    let test_spectra = get_power_spectra(&test[..length], &mt_parms.spectrum)?;
+    if reference_spectra.is_empty() {
+        return Err("Signal is shorter than the analysis window.");
+    }
+    Ok((reference_spectra, test_spectra))
+}
+
+fn get_log_spectral_distance(
+    reference_spectra: &[Vec<f64>],
+    test_spectra: &[Vec<f64>],
+    mt_parms: &MetricsParms,
+) -> f64 {
+    let floor = pow(10.0, mt_parms.floor_db / 10.0);
+    let distances = reference_spectra.iter().zip(test_spectra).map(|(p1, p2)| {
+        let sum: f64 = p1
+            .iter()
+            .zip(p2)
+            .map(|(p1, p2)| {
+                let difference = 10.0 * log10(p1.max(floor) / p2.max(floor));
+                difference * difference
+            })
+            .sum();
+        sqrt(sum / p1.len() as f64)
+    });
+    distances.sum::<f64>() / reference_spectra.len() as f64
+}
+
+fn get_mel_cepstral_distortion(
+    reference_spectra: &[Vec<f64>],
+    test_spectra: &[Vec<f64>],
+    sample_rate: usize,
+    mt_parms: &MetricsParms,
+) -> Result<f64, &'static str> {
+    if sample_rate == 0 {
+        return Err("Invalid sample rate.");
+    }
+    if mt_parms.mel_bands == 0 || mt_parms.cepstral_order >= mt_parms.mel_bands {
+        return Err("Invalid number of mel bands or cepstral coefficients.");
+    }
+    let filters = get_mel_filters(mt_parms.spectrum.fft_size, sample_rate, mt_parms.mel_bands);
+    let floor = pow(10.0, mt_parms.floor_db / 10.0);
+    let get_cepstrum = |power: &Vec<f64>| {
+        let log_energies: Vec<f64> = filters
+            .iter()
+            .map(|filter| {
+                let energy: f64 = filter.iter().zip(power).map(|(w, p)| w * p).sum();
+                log(energy.max(floor))
+            })
+            .collect();
+        let bands = log_energies.len() as f64;
+        (1..=mt_parms.cepstral_order)
+            .map(|k| {
+                log_energies
+                    .iter()
+                    .enumerate()
+                    .map(|(m, e)| e * cos(PI * k as f64 * (m as f64 + 0.5) / bands))
+                    .sum::<f64>()
+            })
+            .collect::<Vec<f64>>()
+    };
+    let distortions = reference_spectra.iter().zip(test_spectra).map(|(p1, p2)| {
+        let sum: f64 = get_cepstrum(p1)
+            .iter()
+            .zip(get_cepstrum(p2))
+            .map(|(c1, c2)| (c1 - c2) * (c1 - c2))
+            .sum();
+        10.0 / LN_10 * sqrt(2.0 * sum)
+    });
+    Ok(distortions.sum::<f64>() / reference_spectra.len() as f64)
+}
+
+/// Returns the weights of triangular filters on the mel scale for the bins `0 ..= fft_size / 2`.
+fn get_mel_filters(fft_size: usize, sample_rate: usize, bands: usize) -> Vec<Vec<f64>> {
+    let max_mel = get_mel(sample_rate as f64 / 2.0);
+    // band edges, equally spaced on the mel scale
+    let edges: Vec<f64> = (0..bands + 2)
+        .map(|i| get_frequency(max_mel * i as f64 / (bands + 1) as f64))
+        .collect();
+    let bin_frequencies = get_bin_frequencies(fft_size, sample_rate);
+    edges
+        .windows(3)
+        .map(|edge| {
+            let (low, center, high) = (edge[0], edge[1], edge[2]);
+            let mut weights = vec![0.0; bin_frequencies.len()];
+            for (w, f) in weights.iter_mut().zip(&bin_frequencies) {
+                if *f > low && *f <= center {
+                    *w = (f - low) / (center - low);
+                } else if *f > center && *f < high {
+                    *w = (high - f) / (high - center);
+                }
+            }
+            weights
+        })
+        .collect()
+}
+
+fn get_mel(frequency: f64) -> f64 {
+    2595.0 * log10(1.0 + frequency / 700.0)
+}
+
+fn get_frequency(mel: f64) -> f64 {
+    700.0 * (pow(10.0, mel / 2595.0) - 1.0)
+}
+
+/// Returns the RMS f0 difference and the voicing error rate.
+fn get_f0_errors(
+    reference: &[f64],
+    test: &[f64],
+    sample_rate: usize,
+    mt_parms: &MetricsParms,
+) -> Result<(f64, f64), &'static str> {
+    let sp_parms = &mt_parms.spectrum;
+    if sp_parms.window_length == 0 || sp_parms.hop_length == 0 {
+        return Err("Invalid window or hop length.");
+    }
+    let length = reference.len().min(test.len());
+    if length < sp_parms.window_length {
+        return Err("Signal is shorter than the analysis window.");
+    }
+    let frame_count = (length - sp_parms.window_length) / sp_parms.hop_length + 1;
+    let mut sum = 0.0;
+    let mut voiced_count = 0;
+    let mut voicing_errors = 0;
+    for i in 0..frame_count {
+        let center = i * sp_parms.hop_length + sp_parms.window_length / 2;
+        let p1 = estimate_pitch(reference, sample_rate, center, &mt_parms.pitch_parms)?;
+        let p2 = estimate_pitch(test, sample_rate, center, &mt_parms.pitch_parms)?;
+        match (p1.is_voiced(), p2.is_voiced()) {
+            (true, true) => {
+                sum += (p1.f0 - p2.f0) * (p1.f0 - p2.f0);
+                voiced_count += 1;
+            }
+            (false, false) => {}
+            _ => voicing_errors += 1,
+        }
+    }
+    let rmse = if voiced_count == 0 {
+        f64::NAN
+    } else {
+        sqrt(sum / f64::from(voiced_count))
+    };
+    Ok((rmse, f64::from(voicing_errors) / frame_count as f64))
+}
@@ -0,0 +1,276 @@
+//! Analysis-by-synthesis: fitting of the formant parameters to a target spectrum.
+
+use crate::frequency_response::{evaluate_response, FrequencyResponsePoint};
+use crate::klatt::{
+    get_glottal_source_transfer_function_coefficients, get_vocal_tract_second_order_sections,
+    FrameParms, MainParms,
+};
+use crate::math::sqrt;
+use alloc::vec::Vec;
+
+/// Minimum formant frequency in Hz.
+const MIN_FREQUENCY: f64 = 50.0;
+/// Minimum formant bandwidth in Hz.
+const MIN_BANDWIDTH: f64 = 10.0;
+/// Initial relative step of the formant frequencies.
+const FREQUENCY_STEP: f64 = 0.05;
+/// Initial relative step of the formant bandwidths.
+const BANDWIDTH_STEP: f64 = 0.25;
+/// Initial step of the parallel formant levels in dB.
+const LEVEL_STEP: f64 = 3.0;
+/// The search stops when the steps have been halved this many times without an improvement.
+const MAX_STEP_REDUCTIONS: usize = 8;
+
+/// The spectrum of the synthesizer that is compared with a target spectrum.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpectrumModel {
+    /// The vocal tract only, as in `get_vocal_tract_frequency_response`, e.g. for a spectral envelope.
+    VocalTract,
+    /// The vocal tract and the glottal source, as in `get_system_frequency_response`,
+    /// e.g. for the spectrum of a recording.
+    System,
+}
+
+/// Parameters for `fit_frame_parameters`.
+#[derive(Clone, Debug)]
+pub struct OptimizerParms {
+    /// number of oral formants to adjust, starting with F1
+    pub formant_count: usize,
+    /// true = adjust the oral formant frequencies
+    pub optimize_frequencies: bool,
+    /// true = adjust the oral formant bandwidths
+    pub optimize_bandwidths: bool,
+    /// true = adjust the oral formant levels of the parallel branch, if it is enabled
+    pub optimize_levels: bool,
+    /// the spectrum of the synthesizer that is compared with the target
+    pub spectrum_model: SpectrumModel,
+    /// maximum number of passes over all parameters
+    pub max_iterations: usize,
+}
+
+impl Default for OptimizerParms {
+    fn default() -> Self {
+        Self {
+            formant_count: 6,
+            optimize_frequencies: true,
+            optimize_bandwidths: true,
+            optimize_levels: true,
+            spectrum_model: SpectrumModel::VocalTract,
+            max_iterations: 200,
+        }
+    }
+}
+
+/// The result of `fit_frame_parameters`.
+#[derive(Clone)]
+pub struct OptimizationResult {
+    /// the frame parameters with the adjusted formants
+    pub f_parms: FrameParms,
+    /// the spectral distance of the result, see `get_spectral_distance`
+    pub distance: f64,
+    /// the mean level difference in dB between the model and the target.
+    /// Subtracting it from `gain_db` aligns the levels.
+    pub level_offset_db: f64,
+    /// number of passes over all parameters
+    pub iterations: usize,
+}
+
+/// Returns the distance between the spectrum of the synthesizer and a target spectrum.
+///
+/// The distance is the RMS difference of the magnitudes in dB, after the mean difference has been
+/// removed, so the overall level does not matter. Points where the target or the model magnitude
+/// is not finite are ignored. Only the magnitudes of the target points are used.
+///
+/// # Errors
+///
+/// Any invalid parameters will return a static str explaining the invalid param.
+pub fn get_spectral_distance(
+    m_parms: &MainParms,
+    f_parms: &FrameParms,
+    target: &[FrequencyResponsePoint],
+    spectrum_model: SpectrumModel,
+) -> Result<f64, &'static str> {
+    let model = Model::new(m_parms, f_parms, target, spectrum_model)?;
+    Ok(model.get_distance(f_parms)?.0)
+}
+
+/// Adjusts the oral formant frequencies, bandwidths and parallel formant levels of a frame,
+/// to minimize the spectral distance to a target spectrum.
+///
+/// The search starts from `f_parms` and is local, so the initial formants should be close to the
+/// expected ones, e.g. from `analyze` or from a table of vowel formants. The candidates are
+/// evaluated through the second-order sections of the vocal tract. The glottal source does not
+/// depend on the formants, so it is evaluated only once.
+///
+/// A coordinate search is used: each parameter is stepped up and down, and a step is kept if it
+/// reduces the distance. The steps are halved when no parameter can be improved.
+/// Formants with a frequency or bandwidth that is not finite are not adjusted.
+///
+/// # Errors
+///
+/// Any invalid parameters will return a static str explaining the invalid param.
+pub fn fit_frame_parameters(
+    m_parms: &MainParms,
+    f_parms: &FrameParms,
+    target: &[FrequencyResponsePoint],
+    o_parms: &OptimizerParms,
+) -> Result<OptimizationResult, &'static str> {
+    let model = Model::new(m_parms, f_parms, target, o_parms.spectrum_model)?;
+    let max_frequency = m_parms.sample_rate as f64 / 2.0 - MIN_FREQUENCY;
+    let mut best = f_parms.clone();
+    let (mut distance, _) = model.get_distance(&best)?;
+    if distance.is_nan() {
+        return Err("No valid points in the target spectrum.");
+    }
+
+    let formant_count = o_parms
+        .formant_count
+        .min(best.oral_formant_freq.len())
+        .min(best.oral_formant_bw.len());
+    let mut parameters = Vec::new();
+    for i in 0..formant_count {
+        if !best.oral_formant_freq[i].is_finite() || !best.oral_formant_bw[i].is_finite() {
+            continue;
+        }
+        if o_parms.optimize_frequencies {
+            parameters.push((FormantParameter::Frequency(i), FREQUENCY_STEP));
+        }
+        if o_parms.optimize_bandwidths {
+            parameters.push((FormantParameter::Bandwidth(i), BANDWIDTH_STEP));
+        }
+        if o_parms.optimize_levels && best.parallel_enabled && i < best.oral_formant_db.len() {
+            parameters.push((FormantParameter::Level(i), LEVEL_STEP));
+        }
+    }
+
+    let mut iterations = 0;
+    let mut step_reductions = 0;
+    while iterations < o_parms.max_iterations && step_reductions < MAX_STEP_REDUCTIONS {
+        iterations += 1;
+        let mut improved = false;
+        for (parameter, step) in &parameters {
+            for direction in [1.0, -1.0] {
+                let mut candidate = best.clone();
+                if !parameter.apply(&mut candidate, direction * step, max_frequency) {
+                    continue;
+                }
+                let (candidate_distance, _) = model.get_distance(&candidate)?;
+                if candidate_distance < distance {
+                    best = candidate;
+                    distance = candidate_distance;
+                    improved = true;
+                    break;
+                }
+            }
+        }
+        if !improved {
+            for (_, step) in &mut parameters {
+                *step /= 2.0;
+            }
+            step_reductions += 1;
+        }
+    }
+
+    let (distance, level_offset_db) = model.get_distance(&best)?;
+    Ok(OptimizationResult {
+        f_parms: best,
+        distance,
+        level_offset_db,
+        iterations,
+    })
+}
+
+/// A parameter that is adjusted by the optimizer, with the index of the formant.
+#[derive(Clone, Copy)]
+enum FormantParameter {
+    Frequency(usize),
+    Bandwidth(usize),
+    Level(usize),
+}
+
+impl FormantParameter {
+    /// Changes the parameter by a step. Frequencies and bandwidths are changed by a relative step,
+    /// levels by a step in dB. Returns `false` if the changed value is out of range.
+    fn apply(self, f_parms: &mut FrameParms, step: f64, max_frequency: f64) -> bool {
+        match self {
+            FormantParameter::Frequency(i) => {
+                let f = f_parms.oral_formant_freq[i] * (1.0 + step);
+                f_parms.oral_formant_freq[i] = f;
+                (MIN_FREQUENCY..=max_frequency).contains(&f)
+            }
+            FormantParameter::Bandwidth(i) => {
+                let bw = f_parms.oral_formant_bw[i] * (1.0 + step);
+                f_parms.oral_formant_bw[i] = bw;
+                bw >= MIN_BANDWIDTH
+            }
+            FormantParameter::Level(i) => {
+                f_parms.oral_formant_db[i] += step;
+                true
+            }
+        }
+    }
+}
+
+/// The target spectrum and the parts of the model that do not change during the optimization.
+struct Model<'a> {
+    m_parms: &'a MainParms,
+    frequencies: Vec<f64>,
+    target_db: Vec<f64>,
+    /// magnitude of the glottal source in dB, 0 if not included
+    source_db: Vec<f64>,
+}
+
+impl<'a> Model<'a> {
+    fn new(
+        m_parms: &'a MainParms,
+        f_parms: &FrameParms,
+        target: &[FrequencyResponsePoint],
+        spectrum_model: SpectrumModel,
+    ) -> Result<Self, &'static str> {
+        let frequencies: Vec<f64> = target.iter().map(|point| point.frequency).collect();
+        let source_db = if spectrum_model == SpectrumModel::System {
+            let source = get_glottal_source_transfer_function_coefficients(m_parms, f_parms)?;
+            evaluate_response(m_parms.sample_rate, &frequencies, |z1| {
+                source.evaluate_complex(z1)
+            })?
+            .iter()
+            .map(|point| point.magnitude_db)
+            .collect()
+        } else {
+            alloc::vec![0.0; frequencies.len()]
+        };
+        Ok(Self {
+            m_parms,
+            frequencies,
+            target_db: target.iter().map(|point| point.magnitude_db).collect(),
+            source_db,
+        })
+    }
+
+    /// Returns the spectral distance and the mean level difference of a candidate.
+    /// Both are NaN if there are no valid points.
+    fn get_distance(&self, f_parms: &FrameParms) -> Result<(f64, f64), &'static str> {
+        let sections = get_vocal_tract_second_order_sections(self.m_parms, f_parms)?;
+        let response = evaluate_response(self.m_parms.sample_rate, &self.frequencies, |z1| {
+            sections.evaluate_complex(z1)
+        })?;
+        let differences: Vec<f64> = response
+            .iter()
+            .zip(&self.source_db)
+            .zip(&self.target_db)
+            .map(|((point, source_db), target_db)| point.magnitude_db + source_db - target_db)
+            .filter(|difference| difference.is_finite())
+            .collect();
+        if differences.is_empty() {
+            return Ok((f64::NAN, f64::NAN));
+        }
+        let n = differences.len() as f64;
+        let mean = differences.iter().sum::<f64>() / n;
+        let variance = differences
+            .iter()
+            .map(|difference| (difference - mean) * (difference - mean))
+            .sum::<f64>()
+            / n;
+        Ok((sqrt(variance), mean))
+    }
+}
@@ -0,0 +1,130 @@
+//! Computation of the parallel formant levels that match the cascade branch.
+
+use crate::complex::Complex;
+use crate::frequency_response::evaluate_response;
+use crate::klatt::{get_vocal_tract_second_order_sections, FrameParms, MainParms};
+use alloc::{vec, vec::Vec};
+
+const MAX_ITERATIONS: usize = 50;
+/// Level of a muted formant in dB.
+const MUTED_DB: f64 = -99.0;
+/// Maximum remaining level difference in dB, at which the iteration stops.
+const TOLERANCE_DB: f64 = 0.01;
+/// Maximum remaining level difference in dB that is accepted after the last iteration.
+const MAX_ERROR_DB: f64 = 1.0;
+
+/// Returns a copy of the frame parameters with `oral_formant_db` and `nasal_formant_db` set,
+/// so that the response of the parallel branch matches the response of the cascade branch at the
+/// formant frequencies.
+///
+/// Both branches are compared with the same input, i.e. the voicing levels are not taken into account.
+/// The levels of the parallel formants interact, so they are computed iteratively.
+/// Formants without a valid frequency and bandwidth get a level of -99 dB.
+/// Wide high formants can be unreachable, because the parallel branch at their frequency is
+/// dominated by the other formants. These are muted with a level of -99 dB.
+///
+/// # Errors
+///
+/// Any invalid parameters will return a static str explaining the invalid param.
+/// Also returns an error if the levels cannot be matched within 1 dB,
+/// e.g. when formants are so close that the parallel formants cancel out.
+pub fn match_parallel_formant_levels(
+    m_parms: &MainParms,
+    f_parms: &FrameParms,
+) -> Result<FrameParms, &'static str> {
+    let mut f_parms = f_parms.clone();
+    let formant_count = f_parms
+        .oral_formant_freq
+        .len()
+        .min(f_parms.oral_formant_bw.len());
+    let mut frequencies: Vec<f64> = f_parms.oral_formant_freq[..formant_count].to_vec();
+    let has_nasal_formant = f_parms.nasal_formant_freq != 0.0 && f_parms.nasal_formant_bw != 0.0;
+    if has_nasal_formant {
+        frequencies.push(f_parms.nasal_formant_freq);
+    }
+    let mut bandwidths: Vec<f64> = f_parms.oral_formant_bw[..formant_count].to_vec();
+    if has_nasal_formant {
+        bandwidths.push(f_parms.nasal_formant_bw);
+    }
+    let valid: Vec<bool> = frequencies
+        .iter()
+        .zip(&bandwidths)
+        .map(|(f, bw)| f.is_finite() && bw.is_finite() && *bw > 0.0)
+        .collect();
+
+    let cascade = get_branch_levels(m_parms, &f_parms, &frequencies, true)?;
+    // initial levels: the peak gain of a parallel formant is its level
+    let mut levels: Vec<f64> = (0..frequencies.len())
+        .map(|i| if valid[i] { cascade[i] } else { MUTED_DB })
+        .collect();
+    let mut converged = false;
+    let mut max_error = f64::INFINITY;
+    for _ in 0..MAX_ITERATIONS {
+        set_levels(&mut f_parms, &levels, formant_count, has_nasal_formant);
+        let parallel = get_branch_levels(m_parms, &f_parms, &frequencies, false)?;
+        max_error = 0.0;
+        for i in (0..frequencies.len()).filter(|i| valid[*i]) {
+            let error = cascade[i] - parallel[i];
+            if error.is_nan() {
+                return Err("Parallel formant levels cannot be computed.");
+            }
+            if levels[i] <= MUTED_DB && error < 0.0 {
+                // The parallel branch cannot get any lower here, it is dominated by the other formants.
+                continue;
+            }
+            max_error = max_error.max(error.abs());
+            levels[i] = (levels[i] + error).max(MUTED_DB);
+        }
+        if max_error <= TOLERANCE_DB {
+            converged = true;
+            break;
+        }
+    }
+    if !converged && (max_error.is_nan() || max_error > MAX_ERROR_DB) {
+        return Err("Parallel formant levels do not converge.");
+    }
+    set_levels(&mut f_parms, &levels, formant_count, has_nasal_formant);
+    Ok(f_parms)
+}
+
+/// Writes the levels to the frame parameters. The nasal formant level is the last one, if present.
+fn set_levels(
+    f_parms: &mut FrameParms,
+    levels: &[f64],
+    formant_count: usize,
+    has_nasal_formant: bool,
+) {
+    f_parms.oral_formant_db = levels[..formant_count].to_vec();
+    if has_nasal_formant {
+        f_parms.nasal_formant_db = levels[formant_count];
+    }
+}
+
+/// Returns the magnitude of the response of one branch in dB at the specified frequencies.
+fn get_branch_levels(
+    m_parms: &MainParms,
+    f_parms: &FrameParms,
+    frequencies: &[f64],
+    cascade: bool,
+) -> Result<Vec<f64>, &'static str> {
+    let mut branch_parms = f_parms.clone();
+    branch_parms.cascade_enabled = cascade;
+    branch_parms.parallel_enabled = !cascade;
+    branch_parms.cascade_voicing_db = 0.0;
+    branch_parms.parallel_voicing_db = 0.0;
+    branch_parms.parallel_bypass_db = -99.0;
+    let sections = get_vocal_tract_second_order_sections(m_parms, &branch_parms)?;
+    // Invalid formant frequencies are not evaluated, their levels are never used.
+    let valid_frequencies: Vec<f64> = frequencies
+        .iter()
+        .map(|f| if f.is_finite() { *f } else { 0.0 })
+        .collect();
+    let response = evaluate_response(m_parms.sample_rate, &valid_frequencies, |z1: Complex| {
+        sections.evaluate_complex(z1)
+    })?;
+    let mut levels = vec![0.0; frequencies.len()];
+    for (level, point) in levels.iter_mut().zip(response) {
+        *level = point.magnitude_db;
+    }
+    Ok(levels)
+}
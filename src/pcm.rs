@@ -0,0 +1,245 @@
+//! Conversion of the generated `f64` samples to integer PCM and to G.711 µ-law and A-law.
+//!
+//! Samples in the range -1 .. 1 are scaled by `2^(bits - 1)` and clipped, so -1 is the most
+//! negative integer and values close to 1 are clipped to the most positive one.
+
+use crate::math::round;
+use alloc::vec::Vec;
+use rand::Rng;
+
+/// Clip level of the µ-law encoder, in 16 bit PCM.
+const MULAW_CLIP: u32 = 32635;
+/// Bias of the µ-law encoder, in 16 bit PCM.
+const MULAW_BIAS: i32 = 0x84;
+/// Upper ends of the A-law segments, in 13 bit PCM.
+const ALAW_SEGMENT_ENDS: [i32; 8] = [0x1f, 0x3f, 0x7f, 0xff, 0x1ff, 0x3ff, 0x7ff, 0xfff];
+
+/// Converts single samples to an output format, e.g. for `Generator::generate_converted_frame`.
+pub trait SampleConverter<T> {
+    /// Converts a sample, which is nominally in the range -1 .. 1. Out-of-range samples are
+    /// clipped and NaN is converted like 0.
+    fn convert(&mut self, sample: f64) -> T;
+}
+
+/// The dither that is added before the quantization.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dither {
+    /// no dither, the samples are rounded
+    None,
+    /// triangular (TPDF) dither of +-1 LSB, which makes the quantization error independent of the signal
+    Triangular,
+    /// triangular dither with first-order noise shaping, which moves the quantization noise to
+    /// high frequencies
+    NoiseShaped,
+}
+
+/// Quantizes samples to signed integers with 8 to 32 bits, with optional dither.
+///
+/// The quantizer keeps the state of the noise shaping, so a signal that is generated in several
+/// frames should be converted with the same quantizer.
+pub struct Quantizer<R> {
+    bits: u32,
+    dither: Dither,
+    /// quantization error of the previous sample in LSB, for the noise shaping
+    error: f64,
+    rng: R,
+}
+
+impl<R: Rng> Quantizer<R> {
+    /// Creates a quantizer.
+    ///
+    /// ### params
+    ///
+    /// ```text
+    /// bits:   Number of bits of the integers, 8 .. 32, e.g. 16 or 24.
+    /// dither: The dither.
+    /// rng:    The random number generator for the dither. It is not used without dither.
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of bits is not supported.
+    pub fn new(bits: u32, dither: Dither, rng: R) -> Result<Self, &'static str> {
+        if !(8..=32).contains(&bits) {
+            return Err("Invalid number of bits.");
+        }
+        Ok(Self::with_bits(bits, dither, rng))
+    }
+
+    /// Creates a quantizer with a valid number of bits.
+    fn with_bits(bits: u32, dither: Dither, rng: R) -> Self {
+        Self {
+            bits,
+            dither,
+            error: 0.0,
+            rng,
+        }
+    }
+
+    /// Quantizes a sample.
+    pub fn quantize(&mut self, sample: f64) -> i32 {
+        let scale = f64::from(1_u32 << (self.bits - 1));
+        let x = if sample.is_nan() { 0.0 } else { sample * scale };
+        let x = match self.dither {
+            Dither::None => return get_clipped(round(x), scale),
+            Dither::Triangular => x,
+            // the error of the previous sample is subtracted, which shapes the noise by 1 - z^-1
+            Dither::NoiseShaped => x - self.error,
+        };
+        let dither = self.rng.random_range(-0.5..0.5) + self.rng.random_range(-0.5..0.5);
+        let q = round(x + dither).clamp(-scale, scale - 1.0);
+        self.error = q - x;
+        get_clipped(q, scale)
+    }
+}
+
+impl<R: Rng> SampleConverter<i32> for Quantizer<R> {
+    fn convert(&mut self, sample: f64) -> i32 {
+        self.quantize(sample)
+    }
+}
+
+/// Converts to `i16`, the quantizer should have 16 bits.
+impl<R: Rng> SampleConverter<i16> for Quantizer<R> {
+    fn convert(&mut self, sample: f64) -> i16 {
+        get_i16(self.quantize(sample))
+    }
+}
+
+/// Converts samples to G.711 µ-law.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MuLaw;
+
+impl SampleConverter<u8> for MuLaw {
+    fn convert(&mut self, sample: f64) -> u8 {
+        encode_mulaw(sample)
+    }
+}
+
+/// Converts samples to G.711 A-law.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ALaw;
+
+impl SampleConverter<u8> for ALaw {
+    fn convert(&mut self, sample: f64) -> u8 {
+        encode_alaw(sample)
+    }
+}
+
+/// Converts samples to 16 bit PCM.
+#[must_use]
+pub fn to_i16<R: Rng>(samples: &[f64], dither: Dither, rng: R) -> Vec<i16> {
+    let mut quantizer = Quantizer::with_bits(16, dither, rng);
+    samples.iter().map(|x| quantizer.convert(*x)).collect()
+}
+
+/// Converts samples to 24 bit PCM, in the range of 24 bit integers.
+#[must_use]
+pub fn to_i24<R: Rng>(samples: &[f64], dither: Dither, rng: R) -> Vec<i32> {
+    let mut quantizer = Quantizer::with_bits(24, dither, rng);
+    samples.iter().map(|x| quantizer.quantize(*x)).collect()
+}
+
+/// Converts samples to 32 bit PCM.
+#[must_use]
+pub fn to_i32<R: Rng>(samples: &[f64], dither: Dither, rng: R) -> Vec<i32> {
+    let mut quantizer = Quantizer::with_bits(32, dither, rng);
+    samples.iter().map(|x| quantizer.quantize(*x)).collect()
+}
+
+/// Converts samples to G.711 µ-law, e.g. for telephony at a sample rate of 8000 Hz.
+#[must_use]
+pub fn to_mulaw(samples: &[f64]) -> Vec<u8> {
+    samples.iter().map(|x| encode_mulaw(*x)).collect()
+}
+
+/// Converts samples to G.711 A-law, e.g. for telephony at a sample rate of 8000 Hz.
+#[must_use]
+pub fn to_alaw(samples: &[f64]) -> Vec<u8> {
+    samples.iter().map(|x| encode_alaw(*x)).collect()
+}
+
+/// Encodes a sample as G.711 µ-law.
+#[must_use]
+pub fn encode_mulaw(sample: f64) -> u8 {
+    let pcm = get_pcm_value(sample, 16);
+    let magnitude = pcm.unsigned_abs().min(MULAW_CLIP) + MULAW_BIAS.unsigned_abs();
+    // the position of the highest bit, 7 .. 14, is the segment
+    let exponent = magnitude.ilog2() - 7;
+    let mantissa = (magnitude >> (exponent + 3)) & 0x0f;
+    let sign = if pcm < 0 { 0x80 } else { 0 };
+    !get_u8(sign | (exponent << 4) | mantissa)
+}
+
+/// Decodes a G.711 µ-law sample.
+#[must_use]
+pub fn decode_mulaw(code: u8) -> f64 {
+    let code = !code;
+    let exponent = (code >> 4) & 0x07;
+    let mantissa = i32::from(code & 0x0f);
+    let magnitude = (((mantissa << 3) + MULAW_BIAS) << exponent) - MULAW_BIAS;
+    let pcm = if code & 0x80 == 0 {
+        magnitude
+    } else {
+        -magnitude
+    };
+    f64::from(pcm) / 32768.0
+}
+
+/// Encodes a sample as G.711 A-law.
+#[must_use]
+pub fn encode_alaw(sample: f64) -> u8 {
+    // A-law encodes 13 bits
+    let pcm = get_pcm_value(sample, 16) >> 3;
+    let (mask, magnitude) = if pcm >= 0 {
+        (0xd5, pcm)
+    } else {
+        (0x55, -pcm - 1)
+    };
+    let Some(segment) = ALAW_SEGMENT_ENDS.iter().position(|end| magnitude <= *end) else {
+        return 0x7f ^ mask;
+    };
+    let segment = segment as u32;
+    let shift = if segment < 2 { 1 } else { segment };
+    get_u8((segment << 4) | ((magnitude >> shift) & 0x0f).unsigned_abs()) ^ mask
+}
+
+/// Decodes a G.711 A-law sample.
+#[must_use]
+pub fn decode_alaw(code: u8) -> f64 {
+    let code = code ^ 0x55;
+    let mut magnitude = i32::from(code & 0x0f) << 4;
+    let segment = (code & 0x70) >> 4;
+    match segment {
+        0 => magnitude += 8,
+        1 => magnitude += 0x108,
+        _ => magnitude = (magnitude + 0x108) << (segment - 1),
+    }
+    let pcm = if code & 0x80 == 0 {
+        -magnitude
+    } else {
+        magnitude
+    };
+    f64::from(pcm) / 32768.0
+}
+
+/// Converts a sample to a signed integer with the specified number of bits, with rounding and
+/// clipping but without dither.
+pub(crate) fn get_pcm_value(sample: f64, bits: u32) -> i32 {
+    let scale = f64::from(1_u32 << (bits - 1));
+    let x = if sample.is_nan() { 0.0 } else { sample * scale };
+    get_clipped(round(x), scale)
+}
+
+/// Clips an integer value to the range `-scale .. scale - 1`.
+fn get_clipped(x: f64, scale: f64) -> i32 {
+    x.clamp(-scale, scale - 1.0) as i32
+}
+
+fn get_i16(x: i32) -> i16 {
+    i16::try_from(x).unwrap_or(if x < 0 { i16::MIN } else { i16::MAX })
+}
+
+fn get_u8(x: u32) -> u8 {
+    u8::try_from(x).unwrap_or(u8::MAX)
+}
@@ -0,0 +1,214 @@
+//! Fundamental frequency and voicing estimation with the YIN algorithm.
+//!
+//! See: A. de Cheveigné, H. Kawahara, "YIN, a fundamental frequency estimator for speech and music",
+//! J. Acoust. Soc. Am. 111 (4), 2002.
+
+use crate::klatt::get_sample_position;
+use crate::math::log10;
+use alloc::{vec, vec::Vec};
+
+/// Parameters for the pitch estimation.
+#[derive(Clone, Debug)]
+pub struct PitchParms {
+    /// minimum fundamental frequency in Hz
+    pub f0_min: f64,
+    /// maximum fundamental frequency in Hz
+    pub f0_max: f64,
+    /// threshold for the cumulative mean normalized difference, 0 .. 1, typically 0.15.
+    /// Lower values result in fewer voiced frames.
+    pub threshold: f64,
+    /// signals with an RMS level below this level in dB (relative to full scale 1.0) are unvoiced
+    pub silence_db: f64,
+}
+
+impl Default for PitchParms {
+    fn default() -> Self {
+        Self {
+            f0_min: 60.0,
+            f0_max: 500.0,
+            threshold: 0.15,
+            silence_db: -60.0,
+        }
+    }
+}
+
+/// The estimated pitch at one point in time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PitchEstimate {
+    /// time of the center of the analysis window in seconds
+    pub time: f64,
+    /// fundamental frequency in Hz, 0 if unvoiced
+    pub f0: f64,
+    /// periodicity of the signal, 0 .. 1, 1 = perfectly periodic
+    pub confidence: f64,
+}
+
+impl PitchEstimate {
+    /// Returns `true` if the signal is voiced.
+    #[must_use]
+    pub fn is_voiced(&self) -> bool {
+        self.f0 > 0.0
+    }
+}
+
+/// Estimates the fundamental frequency of a signal for a sequence of frames.
+///
+/// Frame `i` covers the time from `i / frame_rate` to `(i + 1) / frame_rate`, the estimate is
+/// made at its center. The frames are the same as those of a `Vec<FrameParms>` with a frame
+/// duration of `1 / frame_rate`.
+///
+/// ### params
+///
+/// ```text
+/// samples:     The signal.
+/// sample_rate: Sample rate of the signal in Hz.
+/// frame_rate:  Number of frames per second.
+/// p_parms:     The pitch estimation parameters.
+/// ```
+///
+/// # Errors
+///
+/// Any invalid parameters will return a static str explaining the invalid param.
+pub fn track_pitch(
+    samples: &[f64],
+    sample_rate: usize,
+    frame_rate: f64,
+    p_parms: &PitchParms,
+) -> Result<Vec<PitchEstimate>, &'static str> {
+    if sample_rate == 0 {
+        return Err("Invalid sample rate.");
+    }
+    if frame_rate.is_nan() || frame_rate <= 0.0 {
+        return Err("Invalid frame rate.");
+    }
+    let fs = sample_rate as f64;
+    let frame_count = get_sample_position(frame_rate, samples.len() as f64 / fs);
+    (0..frame_count)
+        .map(|i| {
+            let start = get_sample_position(fs, i as f64 / frame_rate).min(samples.len());
+            let end = get_sample_position(fs, (i + 1) as f64 / frame_rate).min(samples.len());
+            // `usize::midpoint` needs Rust 1.85, and the sum of two sample positions cannot overflow
+            #[allow(clippy::manual_midpoint)]
+            let center = (start + end) / 2;
+            estimate_pitch(samples, sample_rate, center, p_parms)
+        })
+        .collect()
+}
+
+/// Estimates the fundamental frequency of a signal around a sample position.
+///
+/// The analysis window is two periods of the minimum fundamental frequency long, but at most twice
+/// the length of the signal.
+/// Parts of the window outside of the signal are treated as zeros.
+///
+/// # Errors
+///
+/// Any invalid parameters will return a static str explaining the invalid param.
+pub fn estimate_pitch(
+    samples: &[f64],
+    sample_rate: usize,
+    center: usize,
+    p_parms: &PitchParms,
+) -> Result<PitchEstimate, &'static str> {
+    if sample_rate == 0 {
+        return Err("Invalid sample rate.");
+    }
+    if p_parms.f0_min.is_nan()
+        || p_parms.f0_min <= 0.0
+        || p_parms.f0_max.is_nan()
+        || p_parms.f0_max <= p_parms.f0_min
+    {
+        return Err("Invalid f0 range.");
+    }
+    let fs = sample_rate as f64;
+    // Periods that are longer than the signal cannot be detected, so the lags are limited to its
+    // length. This also limits the size of the analysis window for a tiny `f0_min`.
+    let min_lag = get_sample_position(fs, 1.0 / p_parms.f0_max)
+        .min(samples.len())
+        .max(2);
+    let max_lag = get_sample_position(fs, 1.0 / p_parms.f0_min)
+        .min(samples.len())
+        .max(min_lag + 1);
+    // integration window of one period, followed by the maximum lag
+    let segment: Vec<f64> = (0..2 * max_lag)
+        .map(|i| {
+            (center + i)
+                .checked_sub(max_lag)
+                .and_then(|pos| samples.get(pos))
+                .copied()
+                .unwrap_or(0.0)
+        })
+        .collect();
+    let unvoiced = PitchEstimate {
+        time: center as f64 / fs,
+        f0: 0.0,
+        confidence: 0.0,
+    };
+    let energy: f64 = segment.iter().map(|x| x * x).sum();
+    if energy == 0.0 || 10.0 * log10(energy / segment.len() as f64) < p_parms.silence_db {
+        return Ok(unvoiced);
+    }
+    let d = get_normalized_difference(&segment, max_lag);
+
+    // the first minimum below the threshold, or the global minimum
+    let mut best = None;
+    let mut lag = min_lag;
+    while lag < max_lag {
+        if d[lag] < p_parms.threshold {
+            while lag + 1 < max_lag && d[lag + 1] < d[lag] {
+                lag += 1;
+            }
+            best = Some(lag);
+            break;
+        }
+        lag += 1;
+    }
+    let is_voiced = best.is_some();
+    let best = best.unwrap_or_else(|| {
+        (min_lag..max_lag)
+            .min_by(|i, j| d[*i].total_cmp(&d[*j]))
+            .unwrap_or(min_lag)
+    });
+
+    // parabolic interpolation of the minimum
+    let (y0, y1, y2) = (d[best - 1], d[best], d[best + 1]);
+    let denominator = y0 - 2.0 * y1 + y2;
+    let offset = if denominator > 0.0 {
+        (0.5 * (y0 - y2) / denominator).clamp(-1.0, 1.0)
+    } else {
+        0.0
+    };
+    let confidence = (1.0 - y1).clamp(0.0, 1.0);
+    if !is_voiced {
+        return Ok(PitchEstimate {
+            confidence,
+            ..unvoiced
+        });
+    }
+    Ok(PitchEstimate {
+        f0: fs / (best as f64 + offset),
+        confidence,
+        ..unvoiced
+    })
+}
+
+/// Returns the cumulative mean normalized difference function for the lags `0 ..= max_lag`.
+fn get_normalized_difference(segment: &[f64], max_lag: usize) -> Vec<f64> {
+    let window = segment.len() - max_lag;
+    let mut d = vec![1.0; max_lag + 1];
+    let mut sum = 0.0;
+    for lag in 1..=max_lag {
+        let difference: f64 = segment[..window]
+            .iter()
+            .zip(&segment[lag..lag + window])
+            .map(|(x1, x2)| (x1 - x2) * (x1 - x2))
+            .sum();
+        sum += difference;
+        d[lag] = if sum == 0.0 {
+            1.0
+        } else {
+            difference * lag as f64 / sum
+        };
+    }
+    d
+}
@@ -0,0 +1,156 @@
+//! Extraction of poles and zeros from transfer functions.
+
+use crate::complex::Complex;
+use crate::klatt::{get_vocal_tract_transfer_function_coefficients, FrameParms, MainParms};
+use crate::math::{exp, log, sin};
+use crate::{poly_complex, poly_real, Polynomial, RationalFunction};
+use alloc::{vec, vec::Vec};
+use core::f64::consts::PI;
+
+/// Roots with an imaginary part below this value, relative to their magnitude, are treated as real.
+/// Multiple real roots, e.g. of the output low-pass filter, are only found with reduced accuracy.
+const REAL_ROOT_TOLERANCE: f64 = 1E-6;
+
+/// A pole or a zero of a transfer function, expressed as the frequency and bandwidth of a resonance.
+///
+/// A complex conjugate pair of roots is returned as a single resonance.
+/// Real roots result in a frequency of 0 or half the sample rate.
+/// Roots outside of the unit circle result in a negative bandwidth.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Resonance {
+    /// Frequency in Hz.
+    pub frequency: f64,
+    /// Bandwidth in Hz.
+    pub bandwidth: f64,
+}
+
+/// The poles and zeros of a transfer function, ordered by frequency.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PolesAndZeros {
+    pub poles: Vec<Resonance>,
+    pub zeros: Vec<Resonance>,
+}
+
+/// Returns the poles and zeros of a transfer function.
+/// Poles and zeros at the origin or at infinity of the z-plane (pure delays) are omitted.
+///
+/// ### params
+///
+/// ```text
+/// trans:       The transfer function, a rational function in z^-1.
+/// sample_rate: Sample rate of the filter in Hz.
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the sample rate is 0 or if the root finding fails.
+pub fn get_poles_and_zeros(
+    trans: &RationalFunction,
+    sample_rate: usize,
+) -> Result<PolesAndZeros, &'static str> {
+    if sample_rate == 0 {
+        return Err("Invalid sample rate.");
+    }
+    Ok(PolesAndZeros {
+        poles: get_resonances(trans.denominator().coefficients(), sample_rate)?,
+        zeros: get_resonances(trans.numerator().coefficients(), sample_rate)?,
+    })
+}
+
+/// Converts the roots of a polynomial in z^-1 to resonances.
+pub(crate) fn get_resonances(
+    a: &[f64],
+    sample_rate: usize,
+) -> Result<Vec<Resonance>, &'static str> {
+    let roots = poly_real::find_roots(a, None)?;
+    let mut resonances: Vec<Resonance> = roots
+        .into_iter()
+        .filter(|x| x.abs() != 0.0 && is_upper_half_plane(*x))
+        .map(|x| get_resonance(x, sample_rate))
+        .collect();
+    resonances.sort_by(|r1, r2| {
+        r1.frequency
+            .total_cmp(&r2.frequency)
+            .then(r1.bandwidth.total_cmp(&r2.bandwidth))
+    });
+    Ok(resonances)
+}
+
+/// Returns `true` for real roots and for the member of a complex conjugate pair with a positive frequency.
+/// With `z = 1 / x`, the sign of the imaginary part is inverted.
+fn is_upper_half_plane(x: Complex) -> bool {
+    x.im <= REAL_ROOT_TOLERANCE * x.abs()
+}
+
+/// Converts a root `x` of a polynomial in z^-1 to a resonance.
+fn get_resonance(x: Complex, sample_rate: usize) -> Resonance {
+    let sample_rate = sample_rate as f64;
+    let is_real = x.im.abs() <= REAL_ROOT_TOLERANCE * x.abs();
+    // z = 1 / x => |z| = 1 / |x|, arg(z) = -arg(x)
+    let angle = if is_real {
+        if x.re > 0.0 {
+            0.0
+        } else {
+            PI
+        }
+    } else {
+        -x.arg()
+    };
+    Resonance {
+        frequency: angle * sample_rate / (2.0 * PI),
+        bandwidth: log(x.abs()) * sample_rate / PI,
+    }
+}
+
+/// Returns the poles and zeros of the overall filter, as described by
+/// `get_vocal_tract_transfer_function_coefficients`.
+///
+/// # Errors
+///
+/// Any invalid parameters will return a static str explaining the invalid param.
+pub fn get_vocal_tract_poles_and_zeros(
+    m_parms: &MainParms,
+    f_parms: &FrameParms,
+) -> Result<PolesAndZeros, &'static str> {
+    let trans = get_vocal_tract_transfer_function_coefficients(m_parms, f_parms)?;
+    get_poles_and_zeros(&trans, m_parms.sample_rate)
+}
+
+/// Returns a transfer function with the specified poles and zeros, the inverse of `get_poles_and_zeros`.
+///
+/// Resonances with a frequency of 0 or half the sample rate result in a single real root,
+/// all others in a complex conjugate pair. The numerator and denominator are normalized to a
+/// constant coefficient of 1, i.e. they are products of factors `1 - p * z^-1`.
+///
+/// # Errors
+///
+/// Returns an error if the sample rate is 0.
+pub fn get_transfer_function_from_poles_and_zeros(
+    pz: &PolesAndZeros,
+    sample_rate: usize,
+) -> Result<RationalFunction, &'static str> {
+    if sample_rate == 0 {
+        return Err("Invalid sample rate.");
+    }
+    Ok(RationalFunction::new(
+        expand_resonances(&pz.zeros, sample_rate),
+        expand_resonances(&pz.poles, sample_rate),
+    ))
+}
+
+/// Returns the product of the factors `1 - p * z^-1` for the roots `p` in the z-plane of all resonances.
+fn expand_resonances(resonances: &[Resonance], sample_rate: usize) -> Polynomial {
+    let sample_rate = sample_rate as f64;
+    let one = Complex::from(1.0);
+    let mut a = vec![one];
+    for resonance in resonances {
+        let r = exp(-PI * resonance.bandwidth / sample_rate);
+        let angle = 2.0 * PI * resonance.frequency / sample_rate;
+        let p = Complex::from_polar(r, angle);
+        a = poly_complex::multiply(&a, &[one, -p]);
+        if sin(angle).abs() > REAL_ROOT_TOLERANCE {
+            a = poly_complex::multiply(&a, &[one, -p.conj()]);
+        }
+    }
+    Polynomial::new(poly_complex::to_real(&a))
+}
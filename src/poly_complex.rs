@@ -0,0 +1,45 @@
+//! Arithmetic on complex polynomials, represented by their coefficients in ascending powers.
+
+use crate::complex::Complex;
+use alloc::{vec, vec::Vec};
+
+/// Evaluates a complex polynomial, using Horner's method.
+/// The empty polynomial evaluates to 0.
+#[must_use]
+pub fn evaluate(a: &[Complex], x: Complex) -> Complex {
+    a.iter()
+        .rev()
+        .fold(Complex::default(), |acc, &c| acc * x + c)
+}
+
+/// Multiplies two complex polynomials.
+/// The product with an empty polynomial is empty.
+#[must_use]
+pub fn multiply(a1: &[Complex], a2: &[Complex]) -> Vec<Complex> {
+    if a1.is_empty() || a2.is_empty() {
+        return Vec::new();
+    }
+    let mut a3 = vec![Complex::default(); a1.len() + a2.len() - 1];
+    for (i, &v1) in a1.iter().enumerate() {
+        for (j, &v2) in a2.iter().enumerate() {
+            a3[i + j] = a3[i + j] + v1 * v2;
+        }
+    }
+    a3
+}
+
+/// Returns the monic polynomial with the specified roots, `(x - r1) * (x - r2) * ...`.
+#[must_use]
+pub fn expand_roots(roots: &[Complex]) -> Vec<Complex> {
+    roots.iter().fold(vec![Complex::from(1.0)], |a, &r| {
+        multiply(&a, &[-r, Complex::from(1.0)])
+    })
+}
+
+/// Returns the real parts of the coefficients.
+/// Used for polynomials with real coefficients, e.g. expanded from complex conjugate pairs of roots,
+/// where the imaginary parts only contain rounding errors.
+#[must_use]
+pub(crate) fn to_real(a: &[Complex]) -> Vec<f64> {
+    a.iter().map(|c| c.re).collect()
+}
@@ -1,18 +1,22 @@
+//! Arithmetic on real polynomials, represented by their coefficients in ascending powers.
+//! Empty slices are treated as the zero polynomial.
+
+use crate::complex::Complex;
+use crate::math::pow;
 use alloc::{vec, vec::Vec};
 use core::cmp::{max, min};
+use core::f64::consts::PI;
 use core::{
     iter::Iterator, option::Option, result::Result, result::Result::Err, result::Result::Ok,
 };
 
 /// Returns `true` if two polynomials are equal.
-fn compare_equal(a1: &[f64], a2: &[f64], eps: Option<f64>) -> bool {
+pub fn compare_equal(a1: &[f64], a2: &[f64], eps: Option<f64>) -> bool {
     let eps = eps.unwrap_or(0.0);
-    let n1 = a1.len() - 1;
-    let n2 = a2.len() - 1;
-    let n = max(n1, n2);
-    for i in 0..=n {
-        let v1 = if i <= n1 { a1[i] } else { 0.0 };
-        let v2 = if i <= n2 { a2[i] } else { 0.0 };
+    let n = max(a1.len(), a2.len());
+    for i in 0..n {
+        let v1 = a1.get(i).copied().unwrap_or(0.0);
+        let v2 = a2.get(i).copied().unwrap_or(0.0);
         if (v1 - v2).abs() > eps {
             return false;
         }
@@ -21,26 +25,29 @@ fn compare_equal(a1: &[f64], a2: &[f64], eps: Option<f64>) -> bool {
 }
 
 /// Adds two real polynomials.
-fn add(a1: &[f64], a2: &[f64], eps: Option<f64>) -> Result<Vec<f64>, &'static str> {
-    let n1 = if a1.is_empty() { 0 } else { a1.len() - 1 };
-    let n2 = if a2.is_empty() { 0 } else { a2.len() - 1 };
-    let n3 = max(n1, n2);
-    let mut a3 = vec![0.0; n3 + 1];
-    for i in 0..=n3 {
-        let v1 = if i <= n1 { a1[i] } else { 0.0 };
-        let v2 = if i <= n2 { a2[i] } else { 0.0 };
-        a3[i] = v1 + v2;
+pub fn add(a1: &[f64], a2: &[f64], eps: Option<f64>) -> Vec<f64> {
+    let n = max(a1.len(), a2.len());
+    let mut a3 = vec![0.0; n];
+    for (i, v) in a3.iter_mut().enumerate() {
+        *v = a1.get(i).copied().unwrap_or(0.0) + a2.get(i).copied().unwrap_or(0.0);
     }
     trim(&a3, eps)
 }
 
+/// Negates a real polynomial.
+pub fn negate(a: &[f64]) -> Vec<f64> {
+    a.iter().map(|v| -v).collect()
+}
+
 /// Multiplies two real polynomials.
-fn multiply(a1: &[f64], a2: &[f64], eps: Option<f64>) -> Result<Vec<f64>, &'static str> {
+// fine for us because 0.0 is considered a special value (set by us)
+#[allow(clippy::float_cmp)]
+pub fn multiply(a1: &[f64], a2: &[f64], eps: Option<f64>) -> Vec<f64> {
     if a1.is_empty() || a2.is_empty() {
-        return Err("Zero len() arrays.");
+        return vec![0.0];
     }
     if a1.len() == 1 && a1[0] == 0.0 || a2.len() == 1 && a2[0] == 0.0 {
-        return Ok(vec![0.0]);
+        return vec![0.0];
     }
     let n1 = a1.len() - 1;
     let n2 = a2.len() - 1;
@@ -59,30 +66,28 @@ fn multiply(a1: &[f64], a2: &[f64], eps: Option<f64>) -> Result<Vec<f64>, &'stat
 }
 
 /// Divides two real polynomials.
-/// Returns [quotient, remainder] = [a1 / a2, a1 % a2].
+/// Returns (quotient, remainder) = (a1 / a2, a1 % a2).
+/// A division by the zero polynomial returns a zero quotient and `a1` as the remainder.
 // fine for us because 1.0 is considered a special value (set by us)
 #[allow(clippy::float_cmp)]
-fn divide(a1r: &[f64], a2r: &[f64], eps: Option<f64>) -> Result<Vec<Vec<f64>>, &'static str> {
-    if a1r.is_empty() || a2r.is_empty() {
-        return Err("Zero len() arrays.");
-    }
-    let a1 = trim(a1r, eps)?;
-    let a2 = trim(a2r, eps)?;
+fn divide(a1r: &[f64], a2r: &[f64], eps: Option<f64>) -> (Vec<f64>, Vec<f64>) {
+    let a1 = trim(a1r, eps);
+    let a2 = trim(a2r, eps);
     if a2.len() == 1 {
         if a2[0] == 0.0 {
-            return Err("Polynomial division by zero.");
+            return (vec![0.0], a1);
         }
         if a2[0] == 1.0 {
-            return Ok(vec![a1.clone(), vec![0.0]]);
+            return (a1, vec![0.0]);
         }
-        return Ok(vec![div_by_real(&a1, a2[0]), vec![0.0]]);
+        return (div_by_real(&a1, a2[0]), vec![0.0]);
     }
     let n1 = a1.len() - 1;
     let n2 = a2.len() - 1;
     if n1 < n2 {
-        return Ok(vec![vec![0.0], a1.clone()]);
+        return (vec![0.0], a1);
     }
-    let mut a = a1.clone();
+    let mut a = a1;
 
     let lc2 = a2[n2]; // leading coefficient of a2
     for i in (0..=(n1 - n2)).rev() {
@@ -92,123 +97,254 @@ fn divide(a1r: &[f64], a2r: &[f64], eps: Option<f64>) -> Result<Vec<Vec<f64>>, &
             a[i + j] -= r * a2[j];
         }
     }
-    let quotient = trim(&a[n2..], eps)?;
-    let remainder = trim(&a[0..n2], eps)?;
-    Ok(vec![quotient, remainder])
+    let quotient = trim(&a[n2..], eps);
+    let remainder = trim(&a[0..n2], eps);
+    (quotient, remainder)
 }
 
 /// Returns the monic GCD (greatest common divisor) of two polynomials.
-fn gcd(a1: &[f64], a2: &[f64], eps: Option<f64>) -> Result<Vec<f64>, &'static str> {
-    let mut r1 = trim(a1, eps)?;
-    let mut r2 = trim(a2, eps)?;
-    make_monic(&mut r1)?;
-    make_monic(&mut r2)?;
+/// A remainder is considered to be zero when its coefficients are below `eps` relative to the
+/// coefficients of the dividend, so that rounding errors do not hide common factors.
+/// If one of the polynomials is zero, no common factor is searched for and 1 is returned.
+fn gcd(a1: &[f64], a2: &[f64], eps: Option<f64>) -> Vec<f64> {
+    let mut r1 = trim(a1, eps);
+    let mut r2 = trim(a2, eps);
+    if is_zero(&r1) || is_zero(&r2) {
+        return vec![1.0];
+    }
+    make_monic(&mut r1);
+    make_monic(&mut r2);
     if r1.len() < r2.len() {
         core::mem::swap(&mut r1, &mut r2);
     }
     loop {
         if r2.len() < 2 {
             // GCD is 1
-            return Ok(vec![1.0]);
+            return vec![1.0];
+        }
+        let (_, mut r) = divide(&r1, &r2, eps);
+        if is_negligible(&r, &r1, eps) {
+            return r2;
         }
-        let mut r = divide(&r1, &r2, eps)?[1].clone();
-        if r.len() == 1 && r[0] == 0.0 {
-            return Ok(r2);
+        if is_zero(&r) {
+            // only possible if `eps` trims more than `is_negligible` ignores
+            return vec![1.0];
         }
-        make_monic(&mut r)?;
+        make_monic(&mut r);
         r1 = r2;
         r2 = r;
     }
 }
 
-/// Trims top order zero coefficients.
-fn trim(a: &[f64], eps: Option<f64>) -> Result<Vec<f64>, &'static str> {
-    let eps = eps.unwrap_or(0.0);
-    if a.is_empty() {
-        return Err("Zero length array.");
+/// Divides two real polynomials, when `a2` is a factor of `a1`.
+/// Returns `None` if the remainder is not negligible.
+///
+/// The quotient is computed starting with the lowest order coefficients.
+/// For transfer functions of stable filters in `z^-1`, the roots of the denominators lie outside
+/// the unit circle, and this order is numerically much more stable than the usual long division.
+fn divide_exact(a1: &[f64], a2: &[f64], eps: Option<f64>) -> Option<Vec<f64>> {
+    let a1 = trim(a1, eps);
+    let a2 = trim(a2, eps);
+    if a1.len() < a2.len() || a2[0] == 0.0 {
+        return None;
+    }
+    let n = a1.len() - a2.len() + 1;
+    let mut q = vec![0.0; n];
+    for k in 0..n {
+        let mut t = a1[k];
+        for j in 1..=min(k, a2.len() - 1) {
+            t -= a2[j] * q[k - j];
+        }
+        q[k] = t / a2[0];
     }
-    if (a[a.len() - 1]).abs() > eps {
-        return Ok(a.to_vec());
+    let remainder = add(&a1, &negate(&multiply(&q, &a2, None)), None);
+    if !is_negligible(&remainder, &a1, eps) {
+        return None;
     }
-    let mut len = a.len() - 1;
+    Some(q)
+}
+
+/// Returns `true` if all coefficients of `a` are within `eps`, relative to the largest coefficient of `reference`.
+fn is_negligible(a: &[f64], reference: &[f64], eps: Option<f64>) -> bool {
+    let eps = eps.unwrap_or(0.0);
+    let scale = reference.iter().fold(0.0_f64, |m, v| m.max(v.abs()));
+    a.iter().all(|v| v.abs() <= eps * scale)
+}
+
+/// Returns `true` for the zero polynomial.
+pub fn is_zero(a: &[f64]) -> bool {
+    a.iter().all(|v| *v == 0.0)
+}
+
+/// Trims top order zero coefficients.
+/// The zero polynomial is returned as `[0.0]`.
+pub fn trim(a: &[f64], eps: Option<f64>) -> Vec<f64> {
+    let eps = eps.unwrap_or(0.0);
+    let mut len = a.len();
     while len > 0 && (a[len - 1]).abs() <= eps {
         len -= 1;
     }
     if len == 0 {
-        return Ok(vec![0.0]);
+        return vec![0.0];
     }
-    let mut a2 = vec![0.0; len];
-    a2[..len].copy_from_slice(&a[..len]);
-    Ok(a2)
+    a[..len].to_vec()
 }
 
 /// Divides the coefficients by the leading coefficient.
+/// The zero polynomial is left unchanged.
 // fine for us because 1.0 is considered a special value (set by us)
 #[allow(clippy::float_cmp)]
-fn make_monic(a: &mut [f64]) -> Result<(), &'static str> {
+fn make_monic(a: &mut [f64]) {
     let len = a.len();
     if len == 0 {
-        return Err("Zero length array.");
+        return;
     }
     let lc = a[len - 1]; // leading coefficient
-    if lc == 1.0 {
-        // already monic
-        return Ok(());
-    }
-    if lc == 0.0 {
-        // not trimmed?
-        return Err("Leading coefficient is zero.");
+    if lc == 1.0 || lc == 0.0 {
+        // already monic, or not trimmed
+        return;
     }
     a[len - 1] = 1.0;
     for a_i in a.iter_mut().take(len - 1) {
         *a_i /= lc;
     }
-    Ok(())
 }
 
 fn div_by_real(a: &[f64], b: f64) -> Vec<f64> {
-    let mut a2 = vec![0.0; a.len()];
-    for i in 0..a.len() {
-        a2[i] = a[i] / b;
-    }
-    a2
+    a.iter().map(|v| v / b).collect()
 }
 
-// fine for us because 1.0 is considered a special value (set by us)
-#[allow(clippy::float_cmp)]
+/// Adds two rational fractions `n1 / d1 + n2 / d2`.
+/// Returns the numerator and denominator of the sum. Common factors of the denominators only appear once.
 pub fn add_fractions(
-    f1: &[Vec<f64>],
-    f2: &[Vec<f64>],
+    (n1, d1): (&[f64], &[f64]),
+    (n2, d2): (&[f64], &[f64]),
     eps: Option<f64>,
-) -> Result<Vec<Vec<f64>>, &'static str> {
-    if compare_equal(&f1[1], &f2[1], eps) {
+) -> (Vec<f64>, Vec<f64>) {
+    if compare_equal(d1, d2, eps) {
         // if same denominator add numerators
-        return Ok(vec![add(&f1[0], &f2[0], eps)?, f1[1].clone()]);
+        return (add(n1, n2, eps), d1.to_vec());
+    }
+    let g = gcd(d1, d2, eps); // GCD of demoninators
+    if g.len() > 1 {
+        // f1 = n1 / (g * q1), f2 = n2 / (g * q2)
+        // => f1 + f2 = (n1 * q2 + n2 * q1) / (g * q1 * q2)
+        if let (Some(q1), Some(q2)) = (divide_exact(d1, &g, eps), divide_exact(d2, &g, eps)) {
+            let top = add(&multiply(n1, &q2, eps), &multiply(n2, &q1, eps), eps);
+            let bottom = multiply(d1, &q2, eps);
+            return reduce_fraction(top, bottom, &g, eps);
+        }
+        // The GCD was a numerical artifact; fall through to the unreduced sum.
     }
-    let g = gcd(&f1[1], &f2[1], eps)?; // GCD of demoninators
-    if g.len() == 1 && g[0] == 1.0 {
-        // if GCD is 1
-        let top = add(
-            &multiply(&f1[0], &f2[1], eps)?,
-            &multiply(&f2[0], &f1[1], eps)?,
-            eps,
-        )?;
-        let bottom = multiply(&f1[1], &f2[1], eps)?;
-        return Ok(vec![top, bottom]);
+    let top = add(&multiply(n1, d2, eps), &multiply(n2, d1, eps), eps);
+    let bottom = multiply(d1, d2, eps);
+    (top, bottom)
+}
+
+/// Cancels the common factors of `top` and `bottom`.
+/// In exact arithmetic, and if both summands are reduced, any common factor is also a factor of `g`,
+/// so only the GCD of `top` and `g` is computed. The GCD is computed numerically, so this only
+/// holds up to `eps`: common factors that are not found within `eps` are not cancelled.
+fn reduce_fraction(
+    top: Vec<f64>,
+    bottom: Vec<f64>,
+    g: &[f64],
+    eps: Option<f64>,
+) -> (Vec<f64>, Vec<f64>) {
+    if is_zero(&top) {
+        return (vec![0.0], vec![1.0]);
     }
+    let h = gcd(&top, g, eps);
+    if h.len() > 1 {
+        if let (Some(top), Some(bottom)) =
+            (divide_exact(&top, &h, eps), divide_exact(&bottom, &h, eps))
+        {
+            return (top, bottom);
+        }
+    }
+    (top, bottom)
+}
 
-    let top = vec![];
-    let bottom = vec![];
+const ROOT_MAX_ITERATIONS: usize = 500;
 
-    Ok(vec![top, bottom])
+/// Returns the complex roots of a real polynomial, using the Aberth-Ehrlich method.
+/// Roots at zero are included, multiple roots are returned multiple times.
+///
+/// # Errors
+///
+/// Returns an error if the polynomial is zero or contains values that are not finite,
+/// or if the iteration does not converge.
+pub fn find_roots(a: &[f64], eps: Option<f64>) -> Result<Vec<Complex>, &'static str> {
+    if a.iter().any(|v| !v.is_finite()) {
+        return Err("Polynomial coefficients are not finite.");
+    }
+    let a = trim(a, eps);
+    if is_zero(&a) {
+        return Err("Zero polynomial has no defined roots.");
+    }
+    // Roots at zero are split off, because the iteration converges slowly on them.
+    let zero_roots = a.iter().take_while(|v| **v == 0.0).count();
+    let mut roots = vec![Complex::new(0.0, 0.0); zero_roots];
+    let a = &a[zero_roots..];
+    let n = a.len() - 1;
+    if n == 0 {
+        return Ok(roots);
+    }
+    // The initial values are spread on a circle, with the geometric mean of the root magnitudes
+    // as the radius. The angle offset avoids symmetries with the real axis.
+    let radius = pow((a[0] / a[n]).abs(), 1.0 / n as f64);
+    let mut z: Vec<Complex> = (0..n)
+        .map(|k| Complex::expj(2.0 * PI * k as f64 / n as f64 + 0.4) * Complex::new(radius, 0.0))
+        .collect();
+    let mut converged = false;
+    for _ in 0..ROOT_MAX_ITERATIONS {
+        converged = true;
+        for k in 0..n {
+            let (p, dp) = evaluate_with_derivative(a, z[k]);
+            if p.abs() == 0.0 {
+                continue;
+            }
+            let ratio = p / dp;
+            let mut sum = Complex::new(0.0, 0.0);
+            for (j, zj) in z.iter().enumerate() {
+                if j != k {
+                    sum = sum + Complex::new(1.0, 0.0) / (z[k] - *zj);
+                }
+            }
+            let offset = ratio / (Complex::new(1.0, 0.0) - ratio * sum);
+            if !offset.re.is_finite() || !offset.im.is_finite() {
+                continue;
+            }
+            z[k] = z[k] - offset;
+            if offset.abs() > 1E-14 * z[k].abs() {
+                converged = false;
+            }
+        }
+        if converged {
+            break;
+        }
+    }
+    if !converged && z.iter().any(|zk| evaluate_relative_residual(a, *zk) > 1E-6) {
+        return Err("Root finding did not converge.");
+    }
+    roots.extend(z);
+    Ok(roots)
 }
 
-pub fn multiply_fractions(
-    f1: &[Vec<f64>],
-    f2: &[Vec<f64>],
-    eps: Option<f64>,
-) -> Result<Vec<Vec<f64>>, &'static str> {
-    let top = multiply(&f1[0], &f2[0], eps)?;
-    let bottom = multiply(&f1[1], &f2[1], eps)?;
-    Ok(vec![top, bottom])
+/// Evaluates a real polynomial and its derivative at a complex point, using Horner's method.
+fn evaluate_with_derivative(a: &[f64], x: Complex) -> (Complex, Complex) {
+    let mut p = Complex::new(0.0, 0.0);
+    let mut dp = Complex::new(0.0, 0.0);
+    for &c in a.iter().rev() {
+        dp = dp * x + p;
+        p = p * x + Complex::new(c, 0.0);
+    }
+    (p, dp)
+}
+
+/// Returns the magnitude of a polynomial at a complex point, relative to the sum of the magnitudes of its terms.
+fn evaluate_relative_residual(a: &[f64], x: Complex) -> f64 {
+    let (p, _) = evaluate_with_derivative(a, x);
+    let scale = a.iter().rev().fold(0.0, |acc, c| acc * x.abs() + c.abs());
+    p.abs() / scale
 }
@@ -0,0 +1,315 @@
+//! Real polynomials and rational functions, used to represent transfer functions in the z-plane.
+
+use crate::complex::Complex;
+use crate::poly_real;
+use alloc::vec::Vec;
+use core::ops::{Add, Div, Mul, Neg, Sub};
+
+/// A real polynomial, with coefficients in ascending powers.
+///
+/// Top order zero coefficients are removed, the zero polynomial has the single coefficient 0.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polynomial {
+    coefficients: Vec<f64>,
+}
+
+impl Polynomial {
+    /// Creates a polynomial from coefficients in ascending powers.
+    /// An empty vector results in the zero polynomial.
+    #[must_use]
+    pub fn new(mut coefficients: Vec<f64>) -> Self {
+        let len = coefficients
+            .iter()
+            .rposition(|c| *c != 0.0)
+            .map_or(0, |i| i + 1);
+        coefficients.truncate(len);
+        if coefficients.is_empty() {
+            coefficients.push(0.0);
+        }
+        Self::from_trimmed(coefficients)
+    }
+
+    fn from_trimmed(coefficients: Vec<f64>) -> Self {
+        Self { coefficients }
+    }
+
+    /// Creates a constant polynomial.
+    #[must_use]
+    pub fn constant(c: f64) -> Self {
+        Self::new(alloc::vec![c])
+    }
+
+    /// Returns the coefficients in ascending powers.
+    #[must_use]
+    pub fn coefficients(&self) -> &[f64] {
+        &self.coefficients
+    }
+
+    /// Returns the degree. The zero polynomial has degree 0.
+    #[must_use]
+    pub fn degree(&self) -> usize {
+        self.coefficients.len() - 1
+    }
+
+    /// Returns `true` for the zero polynomial.
+    #[must_use]
+    pub fn is_zero(&self) -> bool {
+        poly_real::is_zero(&self.coefficients)
+    }
+
+    /// Evaluates the polynomial, using Horner's method.
+    #[must_use]
+    pub fn evaluate(&self, x: f64) -> f64 {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(0.0, |acc, &c| acc * x + c)
+    }
+
+    /// Evaluates the polynomial at a complex point, using Horner's method.
+    #[must_use]
+    pub fn evaluate_complex(&self, x: Complex) -> Complex {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(Complex::new(0.0, 0.0), |acc, &c| {
+                acc * x + Complex::new(c, 0.0)
+            })
+    }
+
+    /// Removes top order coefficients with a magnitude of at most `epsilon`.
+    #[must_use]
+    pub fn trim(&self, epsilon: f64) -> Self {
+        Self::from_trimmed(poly_real::trim(&self.coefficients, Some(epsilon)))
+    }
+}
+
+impl From<Vec<f64>> for Polynomial {
+    fn from(coefficients: Vec<f64>) -> Self {
+        Self::new(coefficients)
+    }
+}
+
+impl Add for &Polynomial {
+    type Output = Polynomial;
+    fn add(self, other: Self) -> Polynomial {
+        Polynomial::from_trimmed(poly_real::add(
+            &self.coefficients,
+            &other.coefficients,
+            None,
+        ))
+    }
+}
+
+impl Sub for &Polynomial {
+    type Output = Polynomial;
+    fn sub(self, other: Self) -> Polynomial {
+        self + &-other
+    }
+}
+
+impl Mul for &Polynomial {
+    type Output = Polynomial;
+    fn mul(self, other: Self) -> Polynomial {
+        Polynomial::from_trimmed(poly_real::multiply(
+            &self.coefficients,
+            &other.coefficients,
+            None,
+        ))
+    }
+}
+
+impl Neg for &Polynomial {
+    type Output = Polynomial;
+    fn neg(self) -> Polynomial {
+        Polynomial::from_trimmed(poly_real::negate(&self.coefficients))
+    }
+}
+
+/// A rational function of two real polynomials.
+///
+/// The epsilon policy controls how the results of arithmetic operations are simplified:
+/// top order coefficients with a magnitude of at most epsilon are removed,
+/// and common factors of denominators are detected with epsilon as relative tolerance.
+/// The result of an operation uses the larger epsilon of both operands.
+/// An epsilon of 0, the default, only simplifies exact results.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RationalFunction {
+    numerator: Polynomial,
+    denominator: Polynomial,
+    epsilon: f64,
+}
+
+impl RationalFunction {
+    /// Creates a rational function `numerator / denominator`.
+    #[must_use]
+    pub fn new(numerator: Polynomial, denominator: Polynomial) -> Self {
+        Self {
+            numerator,
+            denominator,
+            epsilon: 0.0,
+        }
+    }
+
+    /// Creates a constant rational function.
+    #[must_use]
+    pub fn constant(c: f64) -> Self {
+        Self::new(Polynomial::constant(c), Polynomial::constant(1.0))
+    }
+
+    /// Sets the epsilon used to simplify the results of arithmetic operations.
+    #[must_use]
+    pub fn with_epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    #[must_use]
+    pub fn numerator(&self) -> &Polynomial {
+        &self.numerator
+    }
+
+    #[must_use]
+    pub fn denominator(&self) -> &Polynomial {
+        &self.denominator
+    }
+
+    #[must_use]
+    pub fn epsilon(&self) -> f64 {
+        self.epsilon
+    }
+
+    /// Evaluates the rational function. Poles result in infinite or NaN values.
+    #[must_use]
+    pub fn evaluate(&self, x: f64) -> f64 {
+        self.numerator.evaluate(x) / self.denominator.evaluate(x)
+    }
+
+    /// Evaluates the rational function at a complex point, e.g. a transfer function in `z^-1` at
+    /// `z^-1 = e^(-jw)` on the unit circle for its frequency response.
+    #[must_use]
+    pub fn evaluate_complex(&self, x: Complex) -> Complex {
+        self.numerator.evaluate_complex(x) / self.denominator.evaluate_complex(x)
+    }
+
+    /// Removes top order coefficients of the numerator and denominator with a magnitude of at most epsilon.
+    #[must_use]
+    pub fn trim(&self) -> Self {
+        Self {
+            numerator: self.numerator.trim(self.epsilon),
+            denominator: self.denominator.trim(self.epsilon),
+            epsilon: self.epsilon,
+        }
+    }
+
+    /// Returns the epsilon for an operation of `self` with `other`, in the form used by `poly_real`.
+    fn get_operation_eps(&self, other: &Self) -> Option<f64> {
+        let epsilon = self.epsilon.max(other.epsilon);
+        (epsilon > 0.0).then_some(epsilon)
+    }
+}
+
+impl Add for &RationalFunction {
+    type Output = RationalFunction;
+    fn add(self, other: Self) -> RationalFunction {
+        let eps = self.get_operation_eps(other);
+        let (numerator, denominator) = poly_real::add_fractions(
+            (&self.numerator.coefficients, &self.denominator.coefficients),
+            (
+                &other.numerator.coefficients,
+                &other.denominator.coefficients,
+            ),
+            eps,
+        );
+        RationalFunction {
+            numerator: Polynomial::from_trimmed(numerator),
+            denominator: Polynomial::from_trimmed(denominator),
+            epsilon: eps.unwrap_or(0.0),
+        }
+    }
+}
+
+impl Mul for &RationalFunction {
+    type Output = RationalFunction;
+    fn mul(self, other: Self) -> RationalFunction {
+        let eps = self.get_operation_eps(other);
+        RationalFunction {
+            numerator: Polynomial::from_trimmed(poly_real::multiply(
+                &self.numerator.coefficients,
+                &other.numerator.coefficients,
+                eps,
+            )),
+            denominator: Polynomial::from_trimmed(poly_real::multiply(
+                &self.denominator.coefficients,
+                &other.denominator.coefficients,
+                eps,
+            )),
+            epsilon: eps.unwrap_or(0.0),
+        }
+    }
+}
+
+impl Sub for &RationalFunction {
+    type Output = RationalFunction;
+    fn sub(self, other: Self) -> RationalFunction {
+        self + &-other
+    }
+}
+
+impl Neg for &RationalFunction {
+    type Output = RationalFunction;
+    fn neg(self) -> RationalFunction {
+        RationalFunction {
+            numerator: -&self.numerator,
+            denominator: self.denominator.clone(),
+            epsilon: self.epsilon,
+        }
+    }
+}
+
+/// A division by the zero function results in a zero denominator.
+impl Div for &RationalFunction {
+    type Output = RationalFunction;
+    fn div(self, other: Self) -> RationalFunction {
+        let reciprocal = RationalFunction {
+            numerator: other.denominator.clone(),
+            denominator: other.numerator.clone(),
+            epsilon: other.epsilon,
+        };
+        Mul::mul(self, &reciprocal)
+    }
+}
+
+/// Implements an operator for owned values by delegating to the implementation for references.
+macro_rules! forward_owned_binop {
+    ($t:ty, $imp:ident, $method:ident) => {
+        impl $imp for $t {
+            type Output = $t;
+            fn $method(self, other: Self) -> $t {
+                (&self).$method(&other)
+            }
+        }
+    };
+}
+
+forward_owned_binop!(Polynomial, Add, add);
+forward_owned_binop!(Polynomial, Sub, sub);
+forward_owned_binop!(Polynomial, Mul, mul);
+forward_owned_binop!(RationalFunction, Add, add);
+forward_owned_binop!(RationalFunction, Sub, sub);
+forward_owned_binop!(RationalFunction, Mul, mul);
+forward_owned_binop!(RationalFunction, Div, div);
+
+impl Neg for Polynomial {
+    type Output = Polynomial;
+    fn neg(self) -> Polynomial {
+        -&self
+    }
+}
+
+impl Neg for RationalFunction {
+    type Output = RationalFunction;
+    fn neg(self) -> RationalFunction {
+        -&self
+    }
+}
@@ -0,0 +1,407 @@
+//! Rendering of spectrograms and frequency responses as images, for a visual review of parameters.
+//!
+//! The images are returned as bytes or strings, so they can be written to files with any I/O
+//! library, or none at all in a `no_std` environment.
+
+use crate::frequency_response::evaluate_response;
+use crate::klatt::{get_vocal_tract_second_order_sections, FrameParms, MainParms};
+use crate::math::round;
+use crate::spectrum::{spectrogram, SpectrumParms, Window};
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt::Write;
+
+/// Parameters for `render_spectrogram`.
+#[derive(Clone, Copy, Debug)]
+pub struct SpectrogramImageParms {
+    /// parameters of the spectral analysis, one image column per analysis window
+    pub spectrum: SpectrumParms,
+    /// levels this far below the maximum level in dB are white
+    pub dynamic_range_db: f64,
+    /// highest frequency in Hz that is shown, 0 = half the sample rate
+    pub max_frequency: f64,
+}
+
+impl Default for SpectrogramImageParms {
+    fn default() -> Self {
+        Self {
+            spectrum: SpectrumParms {
+                window: Window::Hann,
+                window_length: 256,
+                hop_length: 64,
+                fft_size: 512,
+            },
+            dynamic_range_db: 60.0,
+            max_frequency: 0.0,
+        }
+    }
+}
+
+/// An 8-bit grayscale image, stored row by row from the top.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GrayImage {
+    pub width: usize,
+    pub height: usize,
+    /// `width * height` pixel values, 0 = black, 255 = white
+    pub pixels: Vec<u8>,
+}
+
+impl GrayImage {
+    /// Encodes the image as a binary PGM (portable graymap) file.
+    #[must_use]
+    pub fn to_pgm(&self) -> Vec<u8> {
+        let mut header = String::new();
+        write!(header, "P5\n{} {}\n255\n", self.width, self.height).ok();
+        let mut pgm = header.into_bytes();
+        pgm.extend_from_slice(&self.pixels);
+        pgm
+    }
+
+    /// Encodes the image as a PNG file.
+    ///
+    /// The image data is stored in uncompressed deflate blocks, so no compression library is needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the image is empty, which PNG does not support, or if the number of
+    /// pixels does not match the size.
+    pub fn to_png(&self) -> Result<Vec<u8>, &'static str> {
+        if self.width == 0 || self.height == 0 || self.pixels.len() != self.width * self.height {
+            return Err("Invalid image size.");
+        }
+        let mut png = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+        let mut header = Vec::new();
+        header.extend_from_slice(&get_u32(self.width).to_be_bytes());
+        header.extend_from_slice(&get_u32(self.height).to_be_bytes());
+        // bit depth 8, grayscale, deflate, adaptive filtering, no interlace
+        header.extend_from_slice(&[8, 0, 0, 0, 0]);
+        write_png_chunk(&mut png, *b"IHDR", &header);
+        // each row starts with filter type 0 (none)
+        let mut raw = Vec::with_capacity((self.width + 1) * self.height);
+        for row in self.pixels.chunks(self.width) {
+            raw.push(0);
+            raw.extend_from_slice(row);
+        }
+        write_png_chunk(&mut png, *b"IDAT", &get_zlib_stored(&raw));
+        write_png_chunk(&mut png, *b"IEND", &[]);
+        Ok(png)
+    }
+
+    /// Encodes the image as an SVG file, with the image embedded as a PNG.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the image cannot be encoded as a PNG, see `to_png`.
+    pub fn to_svg(&self) -> Result<String, &'static str> {
+        let png = self.to_png()?;
+        let mut svg = String::new();
+        write!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n\
+             <image width=\"{w}\" height=\"{h}\" preserveAspectRatio=\"none\" \
+             style=\"image-rendering:pixelated\" href=\"data:image/png;base64,{data}\"/>\n</svg>\n",
+            w = self.width,
+            h = self.height,
+            data = get_base64(&png),
+        )
+        .ok();
+        Ok(svg)
+    }
+}
+
+/// Renders the spectrogram of a signal, e.g. the output of `generate_sound`, as a grayscale image.
+///
+/// Each column is one analysis window of `spectrogram`, and each row one frequency bin, with the
+/// lowest frequency at the bottom. The maximum level is black, levels `dynamic_range_db` and more
+/// below the maximum are white.
+///
+/// ### params
+///
+/// ```text
+/// samples:     The signal.
+/// sample_rate: Sample rate of the signal in Hz.
+/// si_parms:    The image parameters.
+/// ```
+///
+/// # Errors
+///
+/// Any invalid parameters will return a static str explaining the invalid param.
+/// Also returns an error if the signal is shorter than one analysis window.
+pub fn render_spectrogram(
+    samples: &[f64],
+    sample_rate: usize,
+    si_parms: &SpectrogramImageParms,
+) -> Result<GrayImage, &'static str> {
+    if sample_rate == 0 {
+        return Err("Invalid sample rate.");
+    }
+    if si_parms.dynamic_range_db.is_nan() || si_parms.dynamic_range_db <= 0.0 {
+        return Err("Invalid dynamic range.");
+    }
+    let rows = spectrogram(samples, &si_parms.spectrum)?;
+    if rows.is_empty() {
+        return Err("Signal is shorter than the analysis window.");
+    }
+    let bin_count = si_parms.spectrum.fft_size / 2 + 1;
+    let height = if si_parms.max_frequency > 0.0 {
+        let bins = si_parms.max_frequency * si_parms.spectrum.fft_size as f64 / sample_rate as f64;
+        (get_index(bins) + 1).min(bin_count)
+    } else {
+        bin_count
+    };
+    let max_db = rows
+        .iter()
+        .flat_map(|row| &row[..height])
+        .copied()
+        .filter(|level| level.is_finite())
+        .fold(f64::NEG_INFINITY, f64::max);
+    let width = rows.len();
+    let mut pixels = vec![255; width * height];
+    if max_db.is_finite() {
+        for (x, row) in rows.iter().enumerate() {
+            for (bin, level) in row[..height].iter().enumerate() {
+                let y = height - 1 - bin;
+                let fraction = ((max_db - level) / si_parms.dynamic_range_db).clamp(0.0, 1.0);
+                pixels[y * width + x] = get_index(255.0 * fraction) as u8;
+            }
+        }
+    }
+    Ok(GrayImage {
+        width,
+        height,
+        pixels,
+    })
+}
+
+/// Parameters for `plot_frequency_response`.
+#[derive(Clone, Copy, Debug)]
+pub struct ResponsePlotParms {
+    /// width of the plot in pixels
+    pub width: usize,
+    /// height of the plot in pixels
+    pub height: usize,
+    /// highest frequency in Hz that is shown, 0 = half the sample rate
+    pub max_frequency: f64,
+    /// range of the magnitude axis in dB, below the maximum of the response
+    pub dynamic_range_db: f64,
+}
+
+impl Default for ResponsePlotParms {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 400,
+            max_frequency: 0.0,
+            dynamic_range_db: 80.0,
+        }
+    }
+}
+
+/// Plots the frequency response of the vocal tract for a frame as an SVG image.
+///
+/// The response is evaluated at one frequency per pixel through the second-order sections of
+/// `get_vocal_tract_second_order_sections`. The requested oral formant frequencies are overlaid
+/// as dashed red lines, the nasal formant and antiformant, if enabled, as dashed blue and green
+/// lines, so deviations of the spectral peaks from the requested formants are easy to spot.
+/// The grid has a line every 1000 Hz and every 10 dB.
+///
+/// ### params
+///
+/// ```text
+/// m_parms: The main parameters.
+/// f_parms: The frame parameters.
+/// rp_parms: The plot parameters.
+/// ```
+///
+/// # Errors
+///
+/// Any invalid parameters will return a static str explaining the invalid param.
+pub fn plot_frequency_response(
+    m_parms: &MainParms,
+    f_parms: &FrameParms,
+    rp_parms: &ResponsePlotParms,
+) -> Result<String, &'static str> {
+    if rp_parms.width < 2 || rp_parms.height < 2 {
+        return Err("Invalid plot size.");
+    }
+    if rp_parms.dynamic_range_db.is_nan() || rp_parms.dynamic_range_db <= 0.0 {
+        return Err("Invalid dynamic range.");
+    }
+    let max_frequency = if rp_parms.max_frequency > 0.0 {
+        rp_parms.max_frequency
+    } else {
+        m_parms.sample_rate as f64 / 2.0
+    };
+    let (width, height) = (rp_parms.width as f64, rp_parms.height as f64);
+    let frequencies: Vec<f64> = (0..rp_parms.width)
+        .map(|x| x as f64 / (width - 1.0) * max_frequency)
+        .collect();
+    let sections = get_vocal_tract_second_order_sections(m_parms, f_parms)?;
+    let response = evaluate_response(m_parms.sample_rate, &frequencies, |z1| {
+        sections.evaluate_complex(z1)
+    })?;
+    let max_db = response
+        .iter()
+        .map(|point| point.magnitude_db)
+        .filter(|level| level.is_finite())
+        .fold(f64::NEG_INFINITY, f64::max);
+    if !max_db.is_finite() {
+        return Err("Frequency response cannot be computed.");
+    }
+    // the maximum, rounded up to the grid
+    let mut top_db = 10.0 * round(max_db / 10.0);
+    if top_db < max_db {
+        top_db += 10.0;
+    }
+    let bottom_db = top_db - rp_parms.dynamic_range_db;
+    let get_x = |frequency: f64| frequency / max_frequency * width;
+    let get_y =
+        |level: f64| ((top_db - level) / rp_parms.dynamic_range_db).clamp(0.0, 1.0) * height;
+
+    let mut svg = String::new();
+    write!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\" font-family=\"sans-serif\" font-size=\"10\">\n\
+         <rect width=\"{width}\" height=\"{height}\" fill=\"white\"/>\n"
+    )
+    .ok();
+    let mut frequency = 0.0;
+    while frequency <= max_frequency {
+        let x = get_x(frequency);
+        writeln!(
+            svg,
+            "<line x1=\"{x:.1}\" y1=\"0\" x2=\"{x:.1}\" y2=\"{height}\" stroke=\"#ddd\"/>\
+             <text x=\"{:.1}\" y=\"{:.1}\">{frequency} Hz</text>",
+            x + 2.0,
+            height - 2.0
+        )
+        .ok();
+        frequency += 1000.0;
+    }
+    let mut level = top_db;
+    while level >= bottom_db {
+        let y = get_y(level);
+        writeln!(
+            svg,
+            "<line x1=\"0\" y1=\"{y:.1}\" x2=\"{width}\" y2=\"{y:.1}\" stroke=\"#ddd\"/>\
+             <text x=\"2\" y=\"{:.1}\">{level} dB</text>",
+            y + 10.0
+        )
+        .ok();
+        level -= 10.0;
+    }
+    let markers = f_parms
+        .oral_formant_freq
+        .iter()
+        .map(|f| (*f, "red"))
+        .chain([
+            (f_parms.nasal_formant_freq, "blue"),
+            (f_parms.nasal_antiformant_freq, "green"),
+        ])
+        .filter(|(f, _)| f.is_finite() && *f > 0.0 && *f <= max_frequency);
+    for (f, color) in markers {
+        let x = get_x(f);
+        writeln!(
+            svg,
+            "<line class=\"formant\" x1=\"{x:.1}\" y1=\"0\" x2=\"{x:.1}\" y2=\"{height}\" \
+             stroke=\"{color}\" stroke-dasharray=\"4 4\"/>"
+        )
+        .ok();
+    }
+    svg.push_str("<polyline fill=\"none\" stroke=\"black\" stroke-width=\"1.5\" points=\"");
+    for point in response.iter().filter(|point| !point.magnitude_db.is_nan()) {
+        write!(
+            svg,
+            "{:.1},{:.1} ",
+            get_x(point.frequency),
+            get_y(point.magnitude_db)
+        )
+        .ok();
+    }
+    svg.push_str("\"/>\n</svg>\n");
+    Ok(svg)
+}
+
+fn write_png_chunk(png: &mut Vec<u8>, chunk_type: [u8; 4], data: &[u8]) {
+    png.extend_from_slice(&get_u32(data.len()).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(&chunk_type);
+    png.extend_from_slice(data);
+    let crc = get_crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wraps data in a zlib stream of uncompressed deflate blocks.
+fn get_zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK_LENGTH: usize = 0xffff;
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_BLOCK_LENGTH).peekable();
+    if blocks.peek().is_none() {
+        zlib.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let length = block.len() as u16;
+        zlib.push(u8::from(blocks.peek().is_none()));
+        zlib.extend_from_slice(&length.to_le_bytes());
+        zlib.extend_from_slice(&(!length).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&get_adler32(data).to_be_bytes());
+    zlib
+}
+
+fn get_crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffff_u32;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 0 {
+                crc >> 1
+            } else {
+                (crc >> 1) ^ 0xedb8_8320
+            };
+        }
+    }
+    !crc
+}
+
+fn get_adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1_u32, 0_u32);
+    for byte in data {
+        a = (a + u32::from(*byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+fn get_base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (u32::from(bytes[0]) << 16) | (u32::from(bytes[1]) << 8) | u32::from(bytes[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(char::from(ALPHABET[(n >> (18 - 6 * i)) as usize & 0x3f]));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Converts a size to the 32 bit integer of the PNG format, saturating at the maximum.
+fn get_u32(size: usize) -> u32 {
+    u32::try_from(size).unwrap_or(u32::MAX)
+}
+
+/// Converts a non-negative value to an index, rounding to the nearest integer.
+#[allow(clippy::cast_sign_loss)]
+fn get_index(x: f64) -> usize {
+    round(x).max(0.0) as usize
+}
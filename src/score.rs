@@ -0,0 +1,323 @@
+//! KLSYN-style parameter timelines.
+//!
+//! Klatt's original synthesizer took a table of "varied parameters":
+//! each varied parameter is given as a list of time/value breakpoints, all other parameters stay constant.
+//! A [`Score`] holds such a timeline and renders it into a sequence of [`FrameParms`].
+
+use crate::klatt::{
+    generate_frames, get_sample_position, interpolate_breakpoints, FrameParms, Generator,
+    MainParms, MAX_ORAL_FORMANTS,
+};
+use alloc::{vec, vec::Vec};
+use core::{iter::Iterator, option::Option, option::Option::None, option::Option::Some};
+use core::{result::Result, result::Result::Err, result::Result::Ok};
+use rand::Rng;
+
+/// A synthesis parameter that can be varied over time.
+/// The names of the corresponding KLSYN parameters are given in brackets.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Parameter {
+    /// fundamental frequency in Hz (F0)
+    F0,
+    /// F0 flutter level, 0 .. 1 (FL)
+    FlutterLevel,
+    /// vibrato rate in Hz
+    VibratoRate,
+    /// vibrato depth in semitones
+    VibratoDepth,
+    /// tremolo rate in Hz
+    TremoloRate,
+    /// tremolo depth, 0 .. 1
+    TremoloDepth,
+    /// maximum F0 drift in semitones
+    DriftLevel,
+    /// relative length of the open phase of the glottis, 0 .. 1 (OQ)
+    OpenPhaseRatio,
+    /// breathiness in dB (ATURB)
+    BreathinessDb,
+    /// spectral tilt in dB (TL)
+    TiltDb,
+    /// overall gain in dB (G0)
+    GainDb,
+    /// RMS level for automatic gain control
+    AgcRmsLevel,
+    /// nasal formant frequency in Hz (FNP)
+    NasalFormantFreq,
+    /// nasal formant bandwidth in Hz (BNP)
+    NasalFormantBw,
+    /// oral formant frequency in Hz, by formant index starting at 0 (F1 .. F6)
+    OralFormantFreq(usize),
+    /// oral formant bandwidth in Hz, by formant index starting at 0 (B1 .. B6)
+    OralFormantBw(usize),
+    /// voicing amplitude for the cascade branch in dB (AV)
+    CascadeVoicingDb,
+    /// aspiration amplitude for the cascade branch in dB (AH)
+    CascadeAspirationDb,
+    /// amplitude modulation factor for aspiration in the cascade branch, 0 .. 1
+    CascadeAspirationMod,
+    /// nasal antiformant frequency in Hz (FNZ)
+    NasalAntiformantFreq,
+    /// nasal antiformant bandwidth in Hz (BNZ)
+    NasalAntiformantBw,
+    /// voicing amplitude for the parallel branch in dB
+    ParallelVoicingDb,
+    /// aspiration amplitude for the parallel branch in dB
+    ParallelAspirationDb,
+    /// amplitude modulation factor for aspiration in the parallel branch, 0 .. 1
+    ParallelAspirationMod,
+    /// frication noise level in dB (AF)
+    FricationDb,
+    /// amplitude modulation factor for frication noise, 0 .. 1
+    FricationMod,
+    /// parallel bypass level in dB (AB)
+    ParallelBypassDb,
+    /// nasal formant level in dB (AN)
+    NasalFormantDb,
+    /// oral formant level in dB, by formant index starting at 0 (A1 .. A6)
+    OralFormantDb(usize),
+}
+impl Parameter {
+    /// Sets the parameter in a set of frame parameters.
+    /// Per-formant vectors are extended with NaN (formant disabled) as needed.
+    /// Formant indices of `MAX_ORAL_FORMANTS` and above are ignored.
+    pub fn apply(self, f_parms: &mut FrameParms, value: f64) {
+        let field = match self {
+            Parameter::F0 => &mut f_parms.f0,
+            Parameter::FlutterLevel => &mut f_parms.flutter_level,
+            Parameter::VibratoRate => &mut f_parms.vibrato_rate,
+            Parameter::VibratoDepth => &mut f_parms.vibrato_depth,
+            Parameter::TremoloRate => &mut f_parms.tremolo_rate,
+            Parameter::TremoloDepth => &mut f_parms.tremolo_depth,
+            Parameter::DriftLevel => &mut f_parms.drift_level,
+            Parameter::OpenPhaseRatio => &mut f_parms.open_phase_ratio,
+            Parameter::BreathinessDb => &mut f_parms.breathiness_db,
+            Parameter::TiltDb => &mut f_parms.tilt_db,
+            Parameter::GainDb => &mut f_parms.gain_db,
+            Parameter::AgcRmsLevel => &mut f_parms.agc_rms_level,
+            Parameter::NasalFormantFreq => &mut f_parms.nasal_formant_freq,
+            Parameter::NasalFormantBw => &mut f_parms.nasal_formant_bw,
+            Parameter::CascadeVoicingDb => &mut f_parms.cascade_voicing_db,
+            Parameter::CascadeAspirationDb => &mut f_parms.cascade_aspiration_db,
+            Parameter::CascadeAspirationMod => &mut f_parms.cascade_aspiration_mod,
+            Parameter::NasalAntiformantFreq => &mut f_parms.nasal_antiformant_freq,
+            Parameter::NasalAntiformantBw => &mut f_parms.nasal_antiformant_bw,
+            Parameter::ParallelVoicingDb => &mut f_parms.parallel_voicing_db,
+            Parameter::ParallelAspirationDb => &mut f_parms.parallel_aspiration_db,
+            Parameter::ParallelAspirationMod => &mut f_parms.parallel_aspiration_mod,
+            Parameter::FricationDb => &mut f_parms.frication_db,
+            Parameter::FricationMod => &mut f_parms.frication_mod,
+            Parameter::ParallelBypassDb => &mut f_parms.parallel_bypass_db,
+            Parameter::NasalFormantDb => &mut f_parms.nasal_formant_db,
+            Parameter::OralFormantFreq(i) => {
+                return set_formant_value(&mut f_parms.oral_formant_freq, i, value)
+            }
+            Parameter::OralFormantBw(i) => {
+                return set_formant_value(&mut f_parms.oral_formant_bw, i, value)
+            }
+            Parameter::OralFormantDb(i) => {
+                return set_formant_value(&mut f_parms.oral_formant_db, i, value)
+            }
+        };
+        *field = value;
+    }
+}
+
+fn set_formant_value(values: &mut Vec<f64>, i: usize, value: f64) {
+    if i >= MAX_ORAL_FORMANTS {
+        return;
+    }
+    if values.len() <= i {
+        values.resize(i + 1, f64::NAN);
+    }
+    values[i] = value;
+}
+
+/// A list of time/value breakpoints for a single parameter.
+/// The value is interpolated linearly between the breakpoints
+/// and held constant before the first and after the last breakpoint.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct ParameterTrack {
+    /// `(time, value)` pairs, time in seconds, ordered by ascending time
+    points: Vec<(f64, f64)>,
+}
+impl ParameterTrack {
+    #[must_use]
+    pub fn new() -> Self {
+        ParameterTrack { points: Vec::new() }
+    }
+
+    /// Adds a breakpoint.
+    /// The breakpoints are kept ordered by time; an existing breakpoint at the same time is replaced.
+    /// ### params
+    /// ```text
+    ///    time = Time in seconds.
+    ///    value = Parameter value at that time.
+    /// ```
+    pub fn add_point(&mut self, time: f64, value: f64) -> &mut Self {
+        let i = self.points.partition_point(|(t, _)| *t < time);
+        match self.points.get_mut(i) {
+            #[allow(clippy::float_cmp)]
+            Some(point) if point.0 == time => point.1 = value,
+            _ => self.points.insert(i, (time, value)),
+        }
+        self
+    }
+
+    /// Returns the breakpoints, ordered by ascending time.
+    #[must_use]
+    pub fn points(&self) -> &[(f64, f64)] {
+        &self.points
+    }
+
+    /// Returns the interpolated value at a time in seconds, or `None` if the track is empty.
+    #[must_use]
+    pub fn value_at(&self, time: f64) -> Option<f64> {
+        interpolate_breakpoints(&self.points, time)
+    }
+
+    /// Returns the time of the last breakpoint, or 0 if the track is empty.
+    #[must_use]
+    pub fn end_time(&self) -> f64 {
+        self.points.last().map_or(0.0, |(t, _)| *t)
+    }
+}
+
+/// A KLSYN-style timeline of varied parameters.
+///
+/// Parameters without a track keep the value of the `base` frame parameters for the whole score.
+pub struct Score {
+    /// values of the parameters that are not varied
+    pub base: FrameParms,
+    /// total duration in seconds
+    pub duration: f64,
+    tracks: Vec<(Parameter, ParameterTrack)>,
+}
+impl Score {
+    /// ### params
+    /// ```text
+    ///    base = Values of the parameters that are not varied. The frame duration is ignored.
+    ///    duration = Total duration in seconds.
+    /// ```
+    #[must_use]
+    pub fn new(base: FrameParms, duration: f64) -> Self {
+        Score {
+            base,
+            duration,
+            tracks: Vec::new(),
+        }
+    }
+
+    /// Returns the track of a parameter, creating an empty one if the parameter is not varied yet.
+    pub fn track_mut(&mut self, parameter: Parameter) -> &mut ParameterTrack {
+        let i = self
+            .tracks
+            .iter()
+            .position(|(p, _)| *p == parameter)
+            .unwrap_or_else(|| {
+                self.tracks.push((parameter, ParameterTrack::new()));
+                self.tracks.len() - 1
+            });
+        &mut self.tracks[i].1
+    }
+
+    /// Returns the track of a parameter, or `None` if the parameter is not varied.
+    #[must_use]
+    pub fn track(&self, parameter: Parameter) -> Option<&ParameterTrack> {
+        self.tracks
+            .iter()
+            .find(|(p, _)| *p == parameter)
+            .map(|(_, track)| track)
+    }
+
+    /// Returns the frame parameters at a point in time.
+    /// ### params
+    /// ```text
+    ///    time = Time in seconds.
+    ///    frame_duration = Duration of the returned frame in seconds.
+    /// ```
+    #[must_use]
+    pub fn frame_at(&self, time: f64, frame_duration: f64) -> FrameParms {
+        let mut f_parms = self.base.clone();
+        f_parms.duration = frame_duration;
+        for (parameter, track) in &self.tracks {
+            if let Some(value) = track.value_at(time) {
+                parameter.apply(&mut f_parms, value);
+            }
+        }
+        f_parms
+    }
+
+    /// Returns an iterator over the frames of the score.
+    /// Each frame is sampled at its start time; the last frame is shortened to end with the score.
+    /// ### params
+    /// ```text
+    ///    frame_rate = Number of frames per second, e.g. 200 for KLSYN's 5 ms update interval.
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a static str if the frame rate is not finite and positive.
+    pub fn frames(&self, frame_rate: f64) -> Result<ScoreFrames<'_>, &'static str> {
+        if !frame_rate.is_finite() || frame_rate <= 0.0 {
+            return Err("Invalid frame rate.");
+        }
+        Ok(ScoreFrames {
+            score: self,
+            frame_duration: 1.0 / frame_rate,
+            frame: 0,
+        })
+    }
+
+    /// Renders the score into a sequence of frames that can be passed to `generate_sound`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a static str if the frame rate is not finite and positive.
+    pub fn render_frames(&self, frame_rate: f64) -> Result<Vec<FrameParms>, &'static str> {
+        Ok(self.frames(frame_rate)?.collect())
+    }
+
+    /// Generates the sound of the score.
+    /// The frames are fed to the generator one by one, without rendering the whole frame sequence first.
+    ///
+    /// # Errors
+    ///
+    /// Returns a static str if the frame rate is invalid, or if there is a problem with the
+    /// `m_parms` or the parameter values.
+    pub fn generate_sound<R: Rng + Clone>(
+        &self,
+        m_parms: &MainParms,
+        frame_rate: f64,
+        rng: R,
+    ) -> Result<Vec<f64>, &'static str> {
+        if !self.duration.is_finite() || self.duration < 0.0 {
+            return Err("Invalid score duration.");
+        }
+        let frames = self.frames(frame_rate)?;
+        let mut generator = Generator::new(m_parms, rng)?;
+        let mut out_buf = vec![0.0; get_sample_position(m_parms.sample_rate as f64, self.duration)];
+        generate_frames(&mut generator, frames, &mut out_buf)?;
+        Ok(out_buf)
+    }
+}
+
+/// Iterator over the frames of a [`Score`], see [`Score::frames`].
+pub struct ScoreFrames<'a> {
+    score: &'a Score,
+    frame_duration: f64,
+    /// index of the next frame
+    frame: usize,
+}
+impl Iterator for ScoreFrames<'_> {
+    type Item = FrameParms;
+
+    fn next(&mut self) -> Option<FrameParms> {
+        let start = self.frame as f64 * self.frame_duration;
+        if !(self.frame_duration > 0.0 && start < self.score.duration) {
+            return None;
+        }
+        let duration = self.frame_duration.min(self.score.duration - start);
+        self.frame += 1;
+        Some(self.score.frame_at(start, duration))
+    }
+}
@@ -0,0 +1,132 @@
+//! Second-order filter sections (biquads), to run the synthesizer filters in external DSP chains.
+
+use crate::complex::Complex;
+use crate::{Polynomial, RationalFunction};
+use alloc::{vec, vec::Vec};
+
+/// A second-order IIR filter section (biquad), in direct form:
+///
+/// ```text
+///    y[n] = b0 * x[n] + b1 * x[n-1] + b2 * x[n-2] - a1 * y[n-1] - a2 * y[n-2]
+/// ```
+///
+/// The coefficient `a0` is always 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SecondOrderSection {
+    pub b0: f64,
+    pub b1: f64,
+    pub b2: f64,
+    pub a1: f64,
+    pub a2: f64,
+}
+
+impl SecondOrderSection {
+    /// A section that only multiplies its input with a constant gain.
+    #[must_use]
+    pub const fn gain(gain: f64) -> Self {
+        Self {
+            b0: gain,
+            b1: 0.0,
+            b2: 0.0,
+            a1: 0.0,
+            a2: 0.0,
+        }
+    }
+
+    /// Returns the section with the numerator coefficients multiplied by `gain`.
+    #[must_use]
+    pub fn scale(self, gain: f64) -> Self {
+        Self {
+            b0: self.b0 * gain,
+            b1: self.b1 * gain,
+            b2: self.b2 * gain,
+            ..self
+        }
+    }
+
+    /// Returns `true` if the output of the section is always 0.
+    #[must_use]
+    pub fn is_zero(&self) -> bool {
+        self.b0 == 0.0 && self.b1 == 0.0 && self.b2 == 0.0
+    }
+
+    /// Evaluates the transfer function of the section at a point `z^-1` of the z-plane.
+    pub(crate) fn evaluate_complex(&self, z1: Complex) -> Complex {
+        let numerator =
+            (Complex::from(self.b2) * z1 + Complex::from(self.b1)) * z1 + Complex::from(self.b0);
+        let denominator =
+            (Complex::from(self.a2) * z1 + Complex::from(self.a1)) * z1 + Complex::from(1.0);
+        numerator / denominator
+    }
+
+    /// Returns the transfer function of the section in the z-plane, as a rational function in `z^-1`.
+    #[must_use]
+    pub fn get_transfer_function_coefficients(&self) -> RationalFunction {
+        RationalFunction::new(
+            Polynomial::new(vec![self.b0, self.b1, self.b2]),
+            Polynomial::new(vec![1.0, self.a1, self.a2]),
+        )
+    }
+}
+
+/// The filters of the vocal tract for a frame, as second-order sections.
+///
+/// The signal flow is:
+///
+/// ```text
+///    voice -> source -+-> cascade (in series) ----+-> output -> out
+///                     +-> parallel (each, summed) -+
+/// ```
+///
+/// The voicing levels, formant levels and the overall gain are included in the section coefficients.
+/// Breathiness, aspiration and frication are not included.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VocalTractSections {
+    /// Sections applied in series to the voice source, before both branches. Empty for no filtering.
+    pub source: Vec<SecondOrderSection>,
+    /// Sections of the cascade branch, applied in series.
+    /// A disabled cascade branch is a single section with gain 0.
+    pub cascade: Vec<SecondOrderSection>,
+    /// Sections of the parallel branch, each applied to the branch input and summed.
+    /// Empty if the parallel branch is disabled.
+    pub parallel: Vec<SecondOrderSection>,
+    /// Sections applied in series to the sum of both branches.
+    pub output: Vec<SecondOrderSection>,
+}
+
+impl VocalTractSections {
+    /// Evaluates the combined transfer function of all sections at a point `z^-1` of the z-plane,
+    /// without expanding it into polynomials.
+    pub(crate) fn evaluate_complex(&self, z1: Complex) -> Complex {
+        let series = |sections: &[SecondOrderSection]| {
+            sections
+                .iter()
+                .fold(Complex::from(1.0), |acc, s| acc * s.evaluate_complex(z1))
+        };
+        let parallel = self
+            .parallel
+            .iter()
+            .fold(Complex::default(), |acc, s| acc + s.evaluate_complex(z1));
+        series(&self.source) * (series(&self.cascade) + parallel) * series(&self.output)
+    }
+
+    /// Returns the combined transfer function of all sections, in the z-plane.
+    #[must_use]
+    pub fn get_transfer_function_coefficients(&self) -> RationalFunction {
+        let series = |sections: &[SecondOrderSection]| {
+            sections
+                .iter()
+                .fold(RationalFunction::constant(1.0), |acc, s| {
+                    &acc * &s.get_transfer_function_coefficients()
+                })
+        };
+        let parallel = self
+            .parallel
+            .iter()
+            .fold(RationalFunction::constant(0.0), |acc, s| {
+                &acc + &s.get_transfer_function_coefficients()
+            });
+        let branches = &series(&self.cascade) + &parallel;
+        &(&series(&self.source) * &branches) * &series(&self.output)
+    }
+}
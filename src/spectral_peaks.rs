@@ -0,0 +1,123 @@
+//! Peak picking on magnitude responses, to measure the effective formants of a frame.
+
+use crate::frequency_response::{evaluate_response, FrequencyGrid, FrequencyResponsePoint};
+use crate::klatt::{get_vocal_tract_second_order_sections, FrameParms, MainParms};
+use alloc::vec::Vec;
+
+/// Number of points per Hz of the grid that is searched by `get_vocal_tract_spectral_peaks`.
+const PEAK_SEARCH_POINTS_PER_HZ: usize = 1;
+
+/// A local maximum of a magnitude response.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpectralPeak {
+    /// Frequency in Hz.
+    pub frequency: f64,
+    /// Distance in Hz between the points where the magnitude has dropped by 3 dB.
+    /// If the magnitude does not drop by 3 dB on one side before it rises again, twice the
+    /// distance on the other side is used. NaN if it drops by 3 dB on neither side.
+    pub bandwidth: f64,
+    /// Magnitude at the peak in dB.
+    pub level_db: f64,
+}
+
+/// Returns the local maxima of a magnitude response, ordered by frequency.
+///
+/// The response must be ordered by frequency. The first and last points are never returned as peaks.
+/// The frequency and level of a peak are refined by parabolic interpolation of the magnitude in dB.
+#[must_use]
+pub fn find_spectral_peaks(response: &[FrequencyResponsePoint]) -> Vec<SpectralPeak> {
+    let mut peaks = Vec::new();
+    for i in 1..response.len().saturating_sub(1) {
+        let (y0, y1, y2) = (
+            response[i - 1].magnitude_db,
+            response[i].magnitude_db,
+            response[i + 1].magnitude_db,
+        );
+        if !(y1 > y0 && y1 >= y2) {
+            continue;
+        }
+        // vertex of the parabola through the three points, as offset in grid steps from the center
+        let curvature = y0 - 2.0 * y1 + y2;
+        let p = if curvature < 0.0 {
+            0.5 * (y0 - y2) / curvature
+        } else {
+            0.0
+        };
+        let frequency = if p >= 0.0 {
+            response[i].frequency + p * (response[i + 1].frequency - response[i].frequency)
+        } else {
+            response[i].frequency + p * (response[i].frequency - response[i - 1].frequency)
+        };
+        let level_db = y1 - 0.25 * (y0 - y2) * p;
+        let lower = find_3db_point(response, i, level_db, false);
+        let upper = find_3db_point(response, i, level_db, true);
+        let bandwidth = match (lower, upper) {
+            (Some(lower), Some(upper)) => upper - lower,
+            (Some(lower), None) => 2.0 * (frequency - lower),
+            (None, Some(upper)) => 2.0 * (upper - frequency),
+            (None, None) => f64::NAN,
+        };
+        peaks.push(SpectralPeak {
+            frequency,
+            bandwidth,
+            level_db,
+        });
+    }
+    peaks
+}
+
+/// Searches from a peak to one side for the frequency where the magnitude has dropped by 3 dB.
+/// Returns `None` if the magnitude rises again before, or if the end of the response is reached.
+fn find_3db_point(
+    response: &[FrequencyResponsePoint],
+    peak: usize,
+    level_db: f64,
+    upwards: bool,
+) -> Option<f64> {
+    let threshold = level_db - 3.0;
+    let mut i = peak;
+    loop {
+        let next = if upwards {
+            (i + 1 < response.len()).then_some(i + 1)
+        } else {
+            i.checked_sub(1)
+        }?;
+        let (p1, p2) = (&response[i], &response[next]);
+        if p2.magnitude_db > p1.magnitude_db {
+            return None;
+        }
+        if p2.magnitude_db <= threshold {
+            // linear interpolation between the two points
+            let t = (p1.magnitude_db - threshold) / (p1.magnitude_db - p2.magnitude_db);
+            return Some(p1.frequency + t * (p2.frequency - p1.frequency));
+        }
+        i = next;
+    }
+}
+
+/// Returns the peaks of the combined response of the cascade and parallel branches for a frame,
+/// i.e. the effective formants, with their -3 dB bandwidths and levels.
+///
+/// The response is evaluated on a grid up to half the sample rate,
+/// using the second-order sections of the vocal tract instead of the expanded transfer function.
+/// Peaks with a bandwidth below a few Hz may be missed.
+///
+/// # Errors
+///
+/// Any invalid parameters will return a static str explaining the invalid param.
+pub fn get_vocal_tract_spectral_peaks(
+    m_parms: &MainParms,
+    f_parms: &FrameParms,
+) -> Result<Vec<SpectralPeak>, &'static str> {
+    let sections = get_vocal_tract_second_order_sections(m_parms, f_parms)?;
+    let frequencies = FrequencyGrid::Linear {
+        start: 0.0,
+        end: m_parms.sample_rate as f64 / 2.0,
+        points: m_parms.sample_rate / 2 * PEAK_SEARCH_POINTS_PER_HZ + 1,
+    }
+    .frequencies()?;
+    let response = evaluate_response(m_parms.sample_rate, &frequencies, |z1| {
+        sections.evaluate_complex(z1)
+    })?;
+    Ok(find_spectral_peaks(&response))
+}
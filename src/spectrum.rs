@@ -0,0 +1,234 @@
+//! Spectral analysis of signals: FFT, window functions, power spectrum, spectrogram and
+//! long-term average spectrum.
+
+use crate::complex::Complex;
+use crate::math::{cos, log10};
+use alloc::{vec, vec::Vec};
+use core::f64::consts::PI;
+
+/// A window function for spectral analysis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Window {
+    Rectangular,
+    Hann,
+    Hamming,
+    Blackman,
+}
+
+impl Window {
+    /// Returns the symmetric window coefficients for a segment of length `n`.
+    #[must_use]
+    pub fn coefficients(self, n: usize) -> Vec<f64> {
+        if n == 1 {
+            return vec![1.0];
+        }
+        (0..n)
+            .map(|i| {
+                let x = 2.0 * PI * i as f64 / (n - 1) as f64;
+                match self {
+                    Window::Rectangular => 1.0,
+                    Window::Hann => 0.5 - 0.5 * cos(x),
+                    Window::Hamming => 0.54 - 0.46 * cos(x),
+                    Window::Blackman => 0.42 - 0.5 * cos(x) + 0.08 * cos(2.0 * x),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Parameters for `spectrogram` and `long_term_average_spectrum`.
+#[derive(Clone, Copy, Debug)]
+pub struct SpectrumParms {
+    /// window function
+    pub window: Window,
+    /// length of the analysis window in samples
+    pub window_length: usize,
+    /// distance between the starts of two analysis windows in samples
+    pub hop_length: usize,
+    /// FFT size, a power of 2 and at least `window_length`. The window is zero-padded to this size.
+    pub fft_size: usize,
+}
+
+/// Computes the discrete Fourier transform in place, with an iterative radix-2 FFT.
+///
+/// # Errors
+///
+/// Returns an error if the length is not a power of 2.
+pub fn fft(buf: &mut [Complex]) -> Result<(), &'static str> {
+    transform(buf, -1.0)
+}
+
+/// Computes the inverse discrete Fourier transform in place, including the scaling by `1 / n`,
+/// so `ifft` reverses `fft`.
+///
+/// # Errors
+///
+/// Returns an error if the length is not a power of 2.
+pub fn ifft(buf: &mut [Complex]) -> Result<(), &'static str> {
+    transform(buf, 1.0)?;
+    let scale = 1.0 / buf.len() as f64;
+    for x in buf.iter_mut() {
+        *x = Complex::new(x.re * scale, x.im * scale);
+    }
+    Ok(())
+}
+
+/// Radix-2 decimation-in-time FFT, `sign` is the sign of the exponent.
+fn transform(buf: &mut [Complex], sign: f64) -> Result<(), &'static str> {
+    let n = buf.len();
+    if !n.is_power_of_two() {
+        return Err("FFT size must be a power of 2.");
+    }
+    // bit reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            buf.swap(i, j);
+        }
+    }
+    let mut length = 2;
+    while length <= n {
+        let w = Complex::expj(sign * 2.0 * PI / length as f64);
+        for start in (0..n).step_by(length) {
+            let mut twiddle = Complex::new(1.0, 0.0);
+            for k in 0..length / 2 {
+                let even = buf[start + k];
+                let odd = buf[start + k + length / 2] * twiddle;
+                buf[start + k] = even + odd;
+                buf[start + k + length / 2] = even - odd;
+                twiddle = twiddle * w;
+            }
+        }
+        length <<= 1;
+    }
+    Ok(())
+}
+
+/// Returns the one-sided power spectrum of a signal segment, for the bins `0 ..= fft_size / 2`.
+///
+/// The segment is windowed and zero-padded to `fft_size`. The power is scaled so that a sinusoid
+/// with an amplitude of 1 at the center frequency of a bin results in a power of 1 in that bin,
+/// the same scaling as in `get_signal_spectrum`.
+///
+/// # Errors
+///
+/// Returns an error if the FFT size is not a power of 2, if the segment is empty or longer than the
+/// FFT size, or if the window is 0 everywhere, e.g. a Hann window of 2 samples.
+pub fn power_spectrum(
+    segment: &[f64],
+    window: Window,
+    fft_size: usize,
+) -> Result<Vec<f64>, &'static str> {
+    if segment.is_empty() || segment.len() > fft_size {
+        return Err("Invalid segment length.");
+    }
+    let coefficients = window.coefficients(segment.len());
+    let window_sum: f64 = coefficients.iter().sum();
+    // the coefficients are at most 1, so a smaller sum only consists of rounding errors
+    if window_sum <= segment.len() as f64 * f64::EPSILON {
+        return Err("Window is too short.");
+    }
+    let scale = 2.0 / window_sum;
+    let mut buf = vec![Complex::default(); fft_size];
+    for (x, (sample, w)) in buf.iter_mut().zip(segment.iter().zip(&coefficients)) {
+        *x = Complex::from(sample * w * scale);
+    }
+    fft(&mut buf)?;
+    Ok(buf[..=fft_size / 2]
+        .iter()
+        .map(|x| x.re * x.re + x.im * x.im)
+        .collect())
+}
+
+/// Returns the log-magnitude spectrogram of a signal, as a matrix of power levels in dB.
+///
+/// Each row is the power spectrum of one analysis window, in the bins `0 ..= fft_size / 2`, see
+/// `power_spectrum`. The windows start at multiples of `hop_length`, and only complete windows are
+/// analyzed. A power of 0 results in negative infinity.
+///
+/// # Errors
+///
+/// Returns an error if the parameters are invalid.
+pub fn spectrogram(
+    samples: &[f64],
+    sp_parms: &SpectrumParms,
+) -> Result<Vec<Vec<f64>>, &'static str> {
+    Ok(get_power_spectra(samples, sp_parms)?
+        .iter()
+        .map(|power| power.iter().map(|p| power_to_db(*p)).collect())
+        .collect())
+}
+
+/// Returns the long-term average spectrum of a signal in dB, the mean power of all analysis
+/// windows of `spectrogram`, in the bins `0 ..= fft_size / 2`.
+///
+/// # Errors
+///
+/// Returns an error if the parameters are invalid or if the signal is shorter than the window.
+pub fn long_term_average_spectrum(
+    samples: &[f64],
+    sp_parms: &SpectrumParms,
+) -> Result<Vec<f64>, &'static str> {
+    let spectra = get_power_spectra(samples, sp_parms)?;
+    if spectra.is_empty() {
+        return Err("Signal is shorter than the analysis window.");
+    }
+    let mut sum = vec![0.0; sp_parms.fft_size / 2 + 1];
+    for power in &spectra {
+        for (s, p) in sum.iter_mut().zip(power) {
+            *s += p;
+        }
+    }
+    let count = spectra.len() as f64;
+    Ok(sum.iter().map(|s| power_to_db(s / count)).collect())
+}
+
+/// Returns the center frequencies in Hz of the bins `0 ..= fft_size / 2`.
+#[must_use]
+pub fn get_bin_frequencies(fft_size: usize, sample_rate: usize) -> Vec<f64> {
+    (0..=fft_size / 2)
+        .map(|k| k as f64 * sample_rate as f64 / fft_size as f64)
+        .collect()
+}
+
+/// Returns the power spectra of all complete analysis windows.
+pub(crate) fn get_power_spectra(
+    samples: &[f64],
+    sp_parms: &SpectrumParms,
+) -> Result<Vec<Vec<f64>>, &'static str> {
+    check_spectrum_parms(sp_parms)?;
+    (0..)
+        .map(|i| i * sp_parms.hop_length)
+        .take_while(|start| start + sp_parms.window_length <= samples.len())
+        .map(|start| {
+            power_spectrum(
+                &samples[start..start + sp_parms.window_length],
+                sp_parms.window,
+                sp_parms.fft_size,
+            )
+        })
+        .collect()
+}
+
+fn check_spectrum_parms(sp_parms: &SpectrumParms) -> Result<(), &'static str> {
+    if sp_parms.window_length == 0 || sp_parms.window_length > sp_parms.fft_size {
+        return Err("Invalid window length.");
+    }
+    if sp_parms.hop_length == 0 {
+        return Err("Invalid hop length.");
+    }
+    if !sp_parms.fft_size.is_power_of_two() {
+        return Err("FFT size must be a power of 2.");
+    }
+    Ok(())
+}
+
+fn power_to_db(power: f64) -> f64 {
+    10.0 * log10(power)
+}
@@ -1,4 +1,4 @@
-use alloc::vec::Vec;
+use crate::RationalFunction;
 
 pub trait Filter {
     fn set_passthrough(&mut self);
@@ -6,9 +6,8 @@ pub trait Filter {
 }
 
 pub trait BasicFilter {
-    /// Returns the polynomial coefficients of the filter transfer function in the z-plane.
-    /// The returned array contains the top and bottom coefficients of the rational fraction, ordered in ascending powers.
-    fn get_transfer_function_coefficients(&self) -> Vec<Vec<f64>>;
+    /// Returns the filter transfer function in the z-plane, as a rational function in `z^-1`.
+    fn get_transfer_function_coefficients(&self) -> RationalFunction;
     /// Perform one step of a filter.
     fn step(&mut self, x: f64) -> f64;
 }
@@ -0,0 +1,99 @@
+//! Formant vocoder: analysis of recorded speech, modification of the frame parameters and resynthesis.
+
+use crate::analysis::{analyze, AnalysisParms};
+use crate::klatt::{generate_sound, F0Contour, FrameParms, MainParms};
+use crate::math::pow;
+use alloc::vec::Vec;
+use rand::Rng;
+
+/// Analyzes a signal, transforms the frame parameters and resynthesizes the result.
+///
+/// The signal is analyzed with `analyze` at the sample rate of `m_parms`, and resynthesized
+/// with `generate_sound`. The transform receives all frames at once, so it can use the context of
+/// neighboring frames. `shift_pitch`, `scale_formants` and `whisper` can be used as transforms,
+/// or combined in a closure.
+///
+/// ### params
+///
+/// ```text
+/// samples:   The signal.
+/// m_parms:   The main parameters of the synthesis. The sample rate is also used for the analysis.
+/// a_parms:   The analysis parameters.
+/// transform: Modifies the analyzed frame parameters before the resynthesis.
+/// rng:       The random number generator for the noise sources.
+/// ```
+///
+/// # Errors
+///
+/// Any invalid parameters will return a static str explaining the invalid param.
+pub fn vocode<R: Rng + Clone>(
+    samples: &[f64],
+    m_parms: &MainParms,
+    a_parms: &AnalysisParms,
+    transform: impl FnOnce(&mut [FrameParms]),
+    rng: R,
+) -> Result<Vec<f64>, &'static str> {
+    let mut frames = analyze(samples, m_parms.sample_rate, a_parms)?;
+    transform(&mut frames);
+    generate_sound(m_parms, &frames, rng)
+}
+
+/// Shifts the fundamental frequency of all voiced frames, including their F0 contours.
+///
+/// ### params
+///
+/// ```text
+/// semitones: The pitch shift in semitones, positive to raise, negative to lower.
+/// ```
+pub fn shift_pitch(frames: &mut [FrameParms], semitones: f64) {
+    let factor = pow(2.0, semitones / 12.0);
+    for f_parms in frames {
+        f_parms.f0 *= factor;
+        match &mut f_parms.f0_contour {
+            Some(F0Contour::Linear { start, end } | F0Contour::Exponential { start, end }) => {
+                *start *= factor;
+                *end *= factor;
+            }
+            Some(F0Contour::Breakpoints(breakpoints)) => {
+                for (_, f0) in breakpoints {
+                    *f0 *= factor;
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+/// Scales the frequencies and bandwidths of the oral formants, e.g. to simulate a shorter
+/// (factor above 1) or longer (factor below 1) vocal tract.
+/// Formants that are shifted to or above half the sample rate are bypassed (set to NaN).
+pub fn scale_formants(frames: &mut [FrameParms], m_parms: &MainParms, factor: f64) {
+    let max_frequency = m_parms.sample_rate as f64 / 2.0;
+    for f_parms in frames {
+        for (f, bw) in f_parms
+            .oral_formant_freq
+            .iter_mut()
+            .zip(&mut f_parms.oral_formant_bw)
+        {
+            *f *= factor;
+            *bw *= factor;
+            if *f >= max_frequency {
+                *f = f64::NAN;
+                *bw = f64::NAN;
+            }
+        }
+    }
+}
+
+/// Converts voiced frames to whispered speech, by replacing the voicing with aspiration.
+pub fn whisper(frames: &mut [FrameParms]) {
+    for f_parms in frames {
+        if f_parms.f0 > 0.0 || f_parms.f0_contour.is_some() {
+            f_parms.f0 = 0.0;
+            f_parms.f0_contour = None;
+            f_parms.cascade_voicing_db = -99.0;
+            f_parms.cascade_aspiration_db = 0.0;
+            f_parms.cascade_aspiration_mod = 0.0;
+        }
+    }
+}
@@ -0,0 +1,379 @@
+//! Encoding and decoding of WAV (RIFF WAVE) files, without I/O dependencies.
+//!
+//! Samples are `f64` in the range -1 .. 1, as returned by `generate_sound`. PCM samples are rounded
+//! and clipped to this range when they are written.
+
+use crate::pcm::get_pcm_value;
+use alloc::{vec, vec::Vec};
+
+/// Length of the header that is written by `encode_wav` and `WavWriter`.
+const HEADER_LENGTH: usize = 44;
+/// Position of the RIFF chunk size in the header.
+const RIFF_SIZE_POSITION: u64 = 4;
+/// Position of the data chunk size in the header.
+const DATA_SIZE_POSITION: u64 = 40;
+const FORMAT_PCM: u16 = 1;
+const FORMAT_IEEE_FLOAT: u16 = 3;
+const FORMAT_EXTENSIBLE: u16 = 0xfffe;
+
+/// The encoding of the samples in a WAV file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WavSampleFormat {
+    /// unsigned 8 bit integers
+    Pcm8,
+    /// signed 16 bit integers
+    Pcm16,
+    /// signed 24 bit integers
+    Pcm24,
+    /// signed 32 bit integers
+    Pcm32,
+    /// 32 bit floating point numbers
+    Float32,
+    /// 64 bit floating point numbers
+    Float64,
+}
+
+impl WavSampleFormat {
+    /// Returns the number of bytes per sample.
+    #[must_use]
+    pub fn bytes_per_sample(self) -> usize {
+        match self {
+            WavSampleFormat::Pcm8 => 1,
+            WavSampleFormat::Pcm16 => 2,
+            WavSampleFormat::Pcm24 => 3,
+            WavSampleFormat::Pcm32 | WavSampleFormat::Float32 => 4,
+            WavSampleFormat::Float64 => 8,
+        }
+    }
+
+    fn format_tag(self) -> u16 {
+        match self {
+            WavSampleFormat::Float32 | WavSampleFormat::Float64 => FORMAT_IEEE_FLOAT,
+            _ => FORMAT_PCM,
+        }
+    }
+}
+
+/// The format of a WAV file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WavSpec {
+    /// sample rate in Hz
+    pub sample_rate: usize,
+    /// number of channels, the samples of all channels are interleaved
+    pub channels: usize,
+    /// encoding of the samples
+    pub sample_format: WavSampleFormat,
+}
+
+/// A destination for `WavWriter`, e.g. a file in a flash file system.
+///
+/// The header is written first with empty sizes, which are patched with `write_at` when the
+/// writer is finalized.
+pub trait WavSink {
+    /// Appends bytes at the end.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes cannot be written.
+    fn append(&mut self, bytes: &[u8]) -> Result<(), &'static str>;
+    /// Overwrites bytes that have already been written, starting at a position from the start.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes cannot be written.
+    fn write_at(&mut self, position: u64, bytes: &[u8]) -> Result<(), &'static str>;
+}
+
+impl WavSink for Vec<u8> {
+    fn append(&mut self, bytes: &[u8]) -> Result<(), &'static str> {
+        self.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    fn write_at(&mut self, position: u64, bytes: &[u8]) -> Result<(), &'static str> {
+        let start = usize::try_from(position).map_err(|_| "Invalid position.")?;
+        self.get_mut(start..start + bytes.len())
+            .ok_or("Invalid position.")?
+            .copy_from_slice(bytes);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl WavSink for std::fs::File {
+    fn append(&mut self, bytes: &[u8]) -> Result<(), &'static str> {
+        std::io::Write::write_all(self, bytes).map_err(|_| "Cannot write to the file.")
+    }
+
+    fn write_at(&mut self, position: u64, bytes: &[u8]) -> Result<(), &'static str> {
+        use std::io::{Seek, SeekFrom, Write};
+        let end = self
+            .stream_position()
+            .map_err(|_| "Cannot seek in the file.")?;
+        self.seek(SeekFrom::Start(position))
+            .map_err(|_| "Cannot seek in the file.")?;
+        self.write_all(bytes)
+            .map_err(|_| "Cannot write to the file.")?;
+        self.seek(SeekFrom::Start(end))
+            .map_err(|_| "Cannot seek in the file.")?;
+        Ok(())
+    }
+}
+
+/// Writes a WAV file in blocks, e.g. frame by frame from a `Generator`, without keeping the whole
+/// signal in memory.
+///
+/// The sizes in the header are patched by `finalize`. A file that is not finalized has empty
+/// sizes, which `decode_wav` reads as "up to the end of the file".
+pub struct WavWriter<S: WavSink> {
+    sink: S,
+    spec: WavSpec,
+    data_length: usize,
+}
+
+impl<S: WavSink> WavWriter<S> {
+    /// Creates a writer and writes the header.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the format is invalid, or if the header cannot be written.
+    pub fn new(mut sink: S, spec: WavSpec) -> Result<Self, &'static str> {
+        sink.append(&get_header(&spec, 0)?)?;
+        Ok(Self {
+            sink,
+            spec,
+            data_length: 0,
+        })
+    }
+
+    /// Appends samples, interleaved if there are several channels.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file would exceed 4 GB, or if the samples cannot be written.
+    pub fn write_samples(&mut self, samples: &[f64]) -> Result<(), &'static str> {
+        let length = samples.len() * self.spec.sample_format.bytes_per_sample();
+        if get_riff_size(self.data_length + length).is_none() {
+            return Err("WAV file too large.");
+        }
+        let mut bytes = Vec::with_capacity(length);
+        encode_samples(samples, self.spec.sample_format, &mut bytes);
+        self.sink.append(&bytes)?;
+        self.data_length += length;
+        Ok(())
+    }
+
+    /// Writes the sizes into the header and returns the sink.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sizes cannot be written.
+    pub fn finalize(mut self) -> Result<S, &'static str> {
+        let data_size = u32::try_from(self.data_length).map_err(|_| "WAV file too large.")?;
+        let riff_size = get_riff_size(self.data_length).ok_or("WAV file too large.")?;
+        if self.data_length % 2 == 1 {
+            // chunks are padded to an even length
+            self.sink.append(&[0])?;
+        }
+        self.sink
+            .write_at(RIFF_SIZE_POSITION, &riff_size.to_le_bytes())?;
+        self.sink
+            .write_at(DATA_SIZE_POSITION, &data_size.to_le_bytes())?;
+        Ok(self.sink)
+    }
+}
+
+/// Encodes samples as a WAV file.
+///
+/// ### params
+///
+/// ```text
+/// samples: The samples, interleaved if there are several channels, e.g. the output of `generate_sound`.
+/// spec:    The format of the file.
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the format is invalid, or if the file would exceed 4 GB.
+pub fn encode_wav(samples: &[f64], spec: &WavSpec) -> Result<Vec<u8>, &'static str> {
+    let mut writer = WavWriter::new(
+        Vec::with_capacity(HEADER_LENGTH + samples.len() * spec.sample_format.bytes_per_sample()),
+        *spec,
+    )?;
+    writer.write_samples(samples)?;
+    writer.finalize()
+}
+
+/// Decodes a WAV file.
+///
+/// PCM with 8, 16, 24 and 32 bits and floating point with 32 and 64 bits are supported, also in
+/// the extensible format. Integer samples are scaled to the range -1 .. 1.
+/// Returns the format and the samples, interleaved if there are several channels.
+///
+/// # Errors
+///
+/// Returns an error if the file is not a valid WAV file, or if its format is not supported.
+pub fn decode_wav(bytes: &[u8]) -> Result<(WavSpec, Vec<f64>), &'static str> {
+    if bytes.len() < 12 || &bytes[..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err("Not a WAV file.");
+    }
+    let mut spec = None;
+    let mut position = 12;
+    while position + 8 <= bytes.len() {
+        let id = &bytes[position..position + 4];
+        let size = read_u32(bytes, position + 4) as usize;
+        let start = position + 8;
+        if id == b"data" {
+            let spec = spec.ok_or("WAV format chunk is missing.")?;
+            // an unfinalized stream has an empty or maximum size
+            let end = if size == 0 || size == u32::MAX as usize {
+                bytes.len()
+            } else {
+                start.saturating_add(size).min(bytes.len())
+            };
+            return Ok((spec, decode_samples(&bytes[start..end], &spec)));
+        }
+        let chunk = bytes
+            .get(start..start.saturating_add(size))
+            .ok_or("Truncated WAV chunk.")?;
+        if id == b"fmt " {
+            spec = Some(decode_format(chunk)?);
+        }
+        // chunks are padded to an even length
+        position = start + size + size % 2;
+    }
+    Err("WAV data chunk is missing.")
+}
+
+fn get_header(spec: &WavSpec, data_length: usize) -> Result<Vec<u8>, &'static str> {
+    let channels = u16::try_from(spec.channels)
+        .ok()
+        .filter(|channels| *channels > 0)
+        .ok_or("Invalid number of channels.")?;
+    let sample_rate = u32::try_from(spec.sample_rate)
+        .ok()
+        .filter(|sample_rate| *sample_rate > 0)
+        .ok_or("Invalid sample rate.")?;
+    let bytes_per_sample = spec.sample_format.bytes_per_sample() as u16;
+    let block_align = channels
+        .checked_mul(bytes_per_sample)
+        .ok_or("Invalid number of channels.")?;
+    let byte_rate = sample_rate
+        .checked_mul(u32::from(block_align))
+        .ok_or("Invalid sample rate.")?;
+    let data_size = u32::try_from(data_length).map_err(|_| "WAV file too large.")?;
+    let riff_size = get_riff_size(data_length).ok_or("WAV file too large.")?;
+
+    let mut header = Vec::with_capacity(HEADER_LENGTH);
+    header.extend_from_slice(b"RIFF");
+    header.extend_from_slice(&riff_size.to_le_bytes());
+    header.extend_from_slice(b"WAVEfmt ");
+    header.extend_from_slice(&16_u32.to_le_bytes());
+    header.extend_from_slice(&spec.sample_format.format_tag().to_le_bytes());
+    header.extend_from_slice(&channels.to_le_bytes());
+    header.extend_from_slice(&sample_rate.to_le_bytes());
+    header.extend_from_slice(&byte_rate.to_le_bytes());
+    header.extend_from_slice(&block_align.to_le_bytes());
+    header.extend_from_slice(&(bytes_per_sample * 8).to_le_bytes());
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&data_size.to_le_bytes());
+    Ok(header)
+}
+
+/// Returns the size of the RIFF chunk for a data chunk of the specified length, if it fits.
+fn get_riff_size(data_length: usize) -> Option<u32> {
+    u32::try_from(HEADER_LENGTH - 8 + data_length + data_length % 2).ok()
+}
+
+fn decode_format(chunk: &[u8]) -> Result<WavSpec, &'static str> {
+    if chunk.len() < 16 {
+        return Err("Invalid WAV format chunk.");
+    }
+    let mut format_tag = read_u16(chunk, 0);
+    if format_tag == FORMAT_EXTENSIBLE {
+        // the format tag is the start of the sub format GUID
+        if chunk.len() < 26 {
+            return Err("Invalid WAV format chunk.");
+        }
+        format_tag = read_u16(chunk, 24);
+    }
+    let channels = usize::from(read_u16(chunk, 2));
+    let sample_rate = read_u32(chunk, 4) as usize;
+    let bits_per_sample = read_u16(chunk, 14);
+    let sample_format = match (format_tag, bits_per_sample) {
+        (FORMAT_PCM, 8) => WavSampleFormat::Pcm8,
+        (FORMAT_PCM, 16) => WavSampleFormat::Pcm16,
+        (FORMAT_PCM, 24) => WavSampleFormat::Pcm24,
+        (FORMAT_PCM, 32) => WavSampleFormat::Pcm32,
+        (FORMAT_IEEE_FLOAT, 32) => WavSampleFormat::Float32,
+        (FORMAT_IEEE_FLOAT, 64) => WavSampleFormat::Float64,
+        _ => return Err("Unsupported WAV sample format."),
+    };
+    if channels == 0 || sample_rate == 0 {
+        return Err("Invalid WAV format chunk.");
+    }
+    Ok(WavSpec {
+        sample_rate,
+        channels,
+        sample_format,
+    })
+}
+
+fn encode_samples(samples: &[f64], sample_format: WavSampleFormat, bytes: &mut Vec<u8>) {
+    for sample in samples {
+        match sample_format {
+            WavSampleFormat::Pcm8 => {
+                bytes.push(u8::try_from(get_pcm_value(*sample, 8) + 128).unwrap_or_default());
+            }
+            WavSampleFormat::Pcm16 => {
+                bytes.extend_from_slice(&(get_pcm_value(*sample, 16) as i16).to_le_bytes());
+            }
+            WavSampleFormat::Pcm24 => {
+                bytes.extend_from_slice(&get_pcm_value(*sample, 24).to_le_bytes()[..3]);
+            }
+            WavSampleFormat::Pcm32 => {
+                bytes.extend_from_slice(&get_pcm_value(*sample, 32).to_le_bytes());
+            }
+            WavSampleFormat::Float32 => bytes.extend_from_slice(&(*sample as f32).to_le_bytes()),
+            WavSampleFormat::Float64 => bytes.extend_from_slice(&sample.to_le_bytes()),
+        }
+    }
+}
+
+#[allow(clippy::cast_possible_wrap)]
+fn decode_samples(data: &[u8], spec: &WavSpec) -> Vec<f64> {
+    let bytes_per_sample = spec.sample_format.bytes_per_sample();
+    // a partial frame at the end is dropped, so the channels stay aligned
+    let frame_length = spec.channels * bytes_per_sample;
+    let data = &data[..data.len() - data.len() % frame_length];
+    let mut samples = vec![0.0; data.len() / bytes_per_sample];
+    for (sample, bytes) in samples.iter_mut().zip(data.chunks_exact(bytes_per_sample)) {
+        *sample = match spec.sample_format {
+            WavSampleFormat::Pcm8 => f64::from(i32::from(bytes[0]) - 128) / 128.0,
+            WavSampleFormat::Pcm16 => f64::from(read_u16(bytes, 0) as i16) / 32768.0,
+            // the 24 bits are shifted into the upper bits, to extend the sign
+            WavSampleFormat::Pcm24 => {
+                f64::from(i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8) / 8_388_608.0
+            }
+            WavSampleFormat::Pcm32 => f64::from(read_u32(bytes, 0) as i32) / 2_147_483_648.0,
+            WavSampleFormat::Float32 => f64::from(f32::from_bits(read_u32(bytes, 0))),
+            WavSampleFormat::Float64 => {
+                f64::from_le_bytes(bytes[..8].try_into().unwrap_or_default())
+            }
+        };
+    }
+    samples
+}
+
+fn read_u16(bytes: &[u8], position: usize) -> u16 {
+    u16::from_le_bytes([bytes[position], bytes[position + 1]])
+}
+
+fn read_u32(bytes: &[u8], position: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[position],
+        bytes[position + 1],
+        bytes[position + 2],
+        bytes[position + 3],
+    ])
+}
@@ -0,0 +1,84 @@
+#![cfg(feature = "analysis")]
+mod common;
+
+use common::{f_params, rng, SAMPLE_RATE};
+use klatt::{analyze, generate_sound, get_lpc_coefficients, AnalysisParms, MainParms};
+
+#[test]
+fn lpc_of_ar_process_is_recovered() {
+    // x[n] = 1.3 x[n-1] - 0.8 x[n-2] + e[n]
+    let mut signal = vec![0.0; 4000];
+    let mut noise: u32 = 1;
+    for i in 2..signal.len() {
+        noise = noise.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        let e = f64::from(noise) / f64::from(u32::MAX) - 0.5;
+        signal[i] = 1.3 * signal[i - 1] - 0.8 * signal[i - 2] + e;
+    }
+    let a = get_lpc_coefficients(&signal, 2).unwrap();
+    assert!((a[0] - 1.0).abs() < f64::EPSILON);
+    assert!((a[1] + 1.3).abs() < 0.02, "{a:?}");
+    assert!((a[2] - 0.8).abs() < 0.02, "{a:?}");
+}
+
+#[test]
+fn formants_and_f0_are_estimated() {
+    let f_parms = f_params(120.0);
+    let sound = generate_sound(&MainParms::default(), &vec![f_parms.clone()], rng()).unwrap();
+    let frames = analyze(&sound, SAMPLE_RATE, &AnalysisParms::default()).unwrap();
+    assert_eq!(frames.len(), 100);
+    for frame in &frames[10..90] {
+        assert!((frame.f0 - 120.0).abs() < 1.0, "f0 {}", frame.f0);
+        assert!(frame.cascade_voicing_db > -99.0);
+        for (estimate, f) in frame
+            .oral_formant_freq
+            .iter()
+            .zip(&f_parms.oral_formant_freq[..2])
+        {
+            assert!(
+                (estimate - f).abs() < 0.1 * f,
+                "formant {estimate} Hz instead of {f} Hz"
+            );
+        }
+    }
+}
+
+#[test]
+fn noise_is_unvoiced() {
+    let mut f_parms = f_params(0.0);
+    f_parms.cascade_voicing_db = -99.0;
+    f_parms.cascade_aspiration_db = 0.0;
+    let sound = generate_sound(&MainParms::default(), &vec![f_parms], rng()).unwrap();
+    let frames = analyze(&sound, SAMPLE_RATE, &AnalysisParms::default()).unwrap();
+    let voiced = frames.iter().filter(|frame| frame.f0 > 0.0).count();
+    assert!(voiced < frames.len() / 10, "{voiced} voiced frames");
+    assert!(frames[50].cascade_aspiration_db > -99.0);
+}
+
+#[test]
+fn silence_is_silent() {
+    let frames = analyze(
+        &[0.0; SAMPLE_RATE / 10],
+        SAMPLE_RATE,
+        &AnalysisParms::default(),
+    )
+    .unwrap();
+    assert_eq!(frames.len(), 10);
+    for frame in &frames {
+        assert!(frame.f0 == 0.0 && frame.agc_rms_level == 0.0);
+        assert!(frame.oral_formant_freq.iter().all(|f| f.is_nan()));
+    }
+}
+
+#[test]
+fn analysis_can_be_resynthesized() {
+    let sound = generate_sound(&MainParms::default(), &vec![f_params(150.0)], rng()).unwrap();
+    let frames = analyze(&sound, SAMPLE_RATE, &AnalysisParms::default()).unwrap();
+    let resynthesized = generate_sound(&MainParms::default(), &frames, rng()).unwrap();
+    assert_eq!(resynthesized.len(), sound.len());
+    let rms = |buf: &[f64]| (buf.iter().map(|x| x * x).sum::<f64>() / buf.len() as f64).sqrt();
+    let (original, copy) = (rms(&sound[4410..39690]), rms(&resynthesized[4410..39690]));
+    assert!(
+        (copy / original - 1.0).abs() < 0.05,
+        "rms {copy} instead of {original}"
+    );
+}
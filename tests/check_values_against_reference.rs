@@ -1,5 +1,10 @@
+// `StepRng` is deprecated without replacement, but it is what the reference output was generated with.
+#![allow(deprecated)]
+mod common;
+
+use common::{m_parms, reference_f_params};
 use hound::WavReader;
-use klatt::{generate_sound, FrameParms, GlottalSourceType, MainParms};
+use klatt::{compare_renders, generate_sound, FrameParms, MetricsParms};
 use rand::rngs::mock::StepRng;
 
 /// When comparing against the reference sample, consider differences in value of:
@@ -8,51 +13,12 @@ use rand::rngs::mock::StepRng;
 /// Traditioanlly, this is called "epsilon".
 const EPSILON: f32 = 1E-10;
 
-fn m_parms() -> MainParms {
-    MainParms {
-        sample_rate: 44100,
-        glottal_source_type: GlottalSourceType::Impulsive,
-    }
-}
-
-fn f_params() -> FrameParms {
-    FrameParms {
-        duration: 1,
-        f0: 247.0,
-        flutter_level: 0.25,
-        open_phase_ratio: 0.7,
-        breathiness_db: -25.0,
-        tilt_db: 0.0,
-        gain_db: -10.0,
-        agc_rms_level: 0.18,
-        nasal_formant_freq: 1.0,
-        nasal_formant_bw: 0.0,
-        oral_formant_freq: vec![520.0, 1006.0, 2831.0, 3168.0, 4135.0, 5020.0],
-        oral_formant_bw: vec![76.0, 102.0, 72.0, 102.0, 816.0, 596.0],
-        cascade_enabled: true,
-        cascade_voicing_db: 0.0,
-        cascade_aspiration_db: -25.0,
-        cascade_aspiration_mod: 0.5,
-        nasal_antiformant_freq: 1.0,
-        nasal_antiformant_bw: 0.0,
-        parallel_enabled: true,
-        parallel_voicing_db: 0.0,
-        parallel_aspiration_db: -25.0,
-        parallel_aspiration_mod: 0.5,
-        frication_db: -30.0,
-        frication_mod: 0.5,
-        parallel_bypass_db: -99.0,
-        nasal_formant_db: 0.0,
-        oral_formant_db: vec![0.0, -8.0, -15.0, -19.0, -30.0, -35.0],
-    }
-}
-
-#[test]
-fn compare_to_reference_audio() {
+/// Compares a rendered frame sample by sample with a wav file.
+fn compare_to_file(path: &str, f_parms: FrameParms) {
     // used for deterministic, portable output
     let rng = StepRng::new(0, 0x12f6);
-    let mut reader = WavReader::open("reference.wav").unwrap();
-    let sound = generate_sound(&m_parms(), &vec![f_params()], rng).unwrap();
+    let mut reader = WavReader::open(path).unwrap();
+    let sound = generate_sound(&m_parms(), &vec![f_parms], rng).unwrap();
     for (i, (maybe_ref_sample, gen_sample)) in reader
         .samples::<f32>()
         .zip(sound.into_iter().map(|sample| sample as f32))
@@ -65,3 +31,38 @@ fn compare_to_reference_audio() {
             "The generated sample is not within epsilon of the reference sample: abs({ref_sample} - {gen_sample}) > {EPSILON}");
     }
 }
+
+#[test]
+fn compare_to_reference_audio() {
+    compare_to_file("reference.wav", reference_f_params());
+}
+
+/// `reference_without_flutter.wav` was rendered by the original implementation. Flutter is the only
+/// part of the reference frame whose output has changed since then: it used to be evaluated at
+/// whole seconds, and with a `sin` that returned the cosine.
+#[test]
+fn compare_to_reference_audio_without_flutter() {
+    let f_parms = FrameParms {
+        flutter_level: 0.0,
+        ..reference_f_params()
+    };
+    compare_to_file("reference_without_flutter.wav", f_parms);
+}
+
+/// Unlike `compare_to_reference_audio`, this tolerates numeric drift, e.g. from a different noise
+/// generator, and only fails on audible differences.
+#[test]
+fn reference_audio_is_perceptually_close() {
+    let rng = StepRng::new(0, 0x12f6);
+    let mut reader = WavReader::open("reference.wav").unwrap();
+    let reference: Vec<f64> = reader
+        .samples::<f32>()
+        .map(|sample| f64::from(sample.unwrap()))
+        .collect();
+    let sound = generate_sound(&m_parms(), &vec![reference_f_params()], rng).unwrap();
+    let metrics = compare_renders(&reference, &sound, 44100, &MetricsParms::default()).unwrap();
+    assert!(metrics.log_spectral_distance < 1.0);
+    assert!(metrics.mel_cepstral_distortion < 0.5);
+    assert!(metrics.f0_rmse < 1.0);
+    assert!(metrics.voicing_error_rate < 0.05);
+}
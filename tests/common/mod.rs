@@ -0,0 +1,117 @@
+//! Parameters and helpers that are shared by the integration tests.
+//! Not every test uses every helper.
+#![allow(dead_code)]
+
+use klatt::{FrameParms, GlottalSourceType, MainParms};
+use rand::RngCore;
+
+pub const SAMPLE_RATE: usize = 44100;
+
+/// Main parameters with the impulsive glottal source, whose pulses are easy to locate.
+pub fn m_parms() -> MainParms {
+    MainParms {
+        sample_rate: SAMPLE_RATE,
+        glottal_source_type: GlottalSourceType::Impulsive,
+        ..MainParms::default()
+    }
+}
+
+/// A one second vowel with cascade voicing only, without noise and modulation.
+pub fn f_params(f0: f64) -> FrameParms {
+    FrameParms {
+        duration: 1.0,
+        f0,
+        f0_contour: None,
+        flutter_level: 0.0,
+        vibrato_rate: 0.0,
+        vibrato_depth: 0.0,
+        tremolo_rate: 0.0,
+        tremolo_depth: 0.0,
+        drift_level: 0.0,
+        open_phase_ratio: 0.7,
+        breathiness_db: -99.0,
+        tilt_db: 0.0,
+        gain_db: 0.0,
+        agc_rms_level: 0.18,
+        nasal_formant_freq: 0.0,
+        nasal_formant_bw: 0.0,
+        oral_formant_freq: vec![520.0, 1006.0, 2831.0, 3168.0, 4135.0, 5020.0],
+        oral_formant_bw: vec![76.0, 102.0, 72.0, 102.0, 816.0, 596.0],
+        cascade_enabled: true,
+        cascade_voicing_db: 0.0,
+        cascade_aspiration_db: -99.0,
+        cascade_aspiration_mod: 0.5,
+        nasal_antiformant_freq: 0.0,
+        nasal_antiformant_bw: 0.0,
+        parallel_enabled: false,
+        parallel_voicing_db: -99.0,
+        parallel_aspiration_db: -99.0,
+        parallel_aspiration_mod: 0.5,
+        frication_db: -99.0,
+        frication_mod: 0.5,
+        parallel_bypass_db: -99.0,
+        nasal_formant_db: -99.0,
+        oral_formant_db: vec![0.0, -8.0, -15.0, -19.0, -30.0, -35.0],
+    }
+}
+
+/// The frame that `reference.wav` was generated from, with noise, flutter and both branches.
+pub fn reference_f_params() -> FrameParms {
+    FrameParms {
+        flutter_level: 0.25,
+        breathiness_db: -25.0,
+        gain_db: -10.0,
+        nasal_formant_freq: 1.0,
+        cascade_aspiration_db: -25.0,
+        nasal_antiformant_freq: 1.0,
+        parallel_enabled: true,
+        parallel_voicing_db: 0.0,
+        parallel_aspiration_db: -25.0,
+        frication_db: -30.0,
+        nasal_formant_db: 0.0,
+        ..f_params(247.0)
+    }
+}
+
+/// A frame with a nasal formant and antiformant, spectral tilt and both branches.
+pub fn nasal_f_params() -> FrameParms {
+    FrameParms {
+        tilt_db: 3.0,
+        gain_db: -10.0,
+        nasal_formant_freq: 270.0,
+        nasal_formant_bw: 100.0,
+        cascade_voicing_db: -3.0,
+        nasal_antiformant_freq: 450.0,
+        nasal_antiformant_bw: 100.0,
+        parallel_enabled: true,
+        parallel_voicing_db: -6.0,
+        parallel_bypass_db: -20.0,
+        nasal_formant_db: -10.0,
+        ..f_params(247.0)
+    }
+}
+
+/// A small xorshift generator, for deterministic and portable noise.
+#[derive(Clone)]
+pub struct XorShiftRng(pub u64);
+
+impl RngCore for XorShiftRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        for byte in dst {
+            *byte = self.next_u32() as u8;
+        }
+    }
+}
+
+pub fn rng() -> XorShiftRng {
+    XorShiftRng(0x2545_f491_4f6c_dd1d)
+}
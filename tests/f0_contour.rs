@@ -0,0 +1,75 @@
+mod common;
+
+use common::{m_parms, rng, SAMPLE_RATE};
+use klatt::{generate_sound, F0Contour, FrameParms};
+
+/// A frame that outputs the bare glottal pulses, without any formants or noise.
+fn f_params(f0_contour: Option<F0Contour>) -> FrameParms {
+    FrameParms {
+        f0_contour,
+        oral_formant_freq: vec![],
+        oral_formant_bw: vec![],
+        ..common::f_params(100.0)
+    }
+}
+
+fn render(f0_contour: Option<F0Contour>) -> Vec<f64> {
+    generate_sound(&m_parms(), &vec![f_params(f0_contour)], rng()).unwrap()
+}
+
+/// Counts the glottal pulses in a signal.
+fn count_pulses(signal: &[f64]) -> usize {
+    let threshold = signal.iter().fold(0.0_f64, |m, s| m.max(s.abs())) / 4.0;
+    let mut count = 0;
+    let mut high = false;
+    for s in signal {
+        if !high && *s > threshold {
+            count += 1;
+            high = true;
+        } else if high && *s < 0.0 {
+            high = false;
+        }
+    }
+    count
+}
+
+#[test]
+fn constant_contour_matches_f0() {
+    let contour = render(Some(F0Contour::Linear {
+        start: 100.0,
+        end: 100.0,
+    }));
+    assert!(contour == render(None));
+}
+
+#[test]
+fn linear_ramp_raises_pitch() {
+    let sound = render(Some(F0Contour::Linear {
+        start: 100.0,
+        end: 200.0,
+    }));
+    let tenth = SAMPLE_RATE / 10;
+    let first = count_pulses(&sound[..tenth]);
+    let last = count_pulses(&sound[SAMPLE_RATE - tenth..]);
+    assert!((10..=11).contains(&first), "{first} pulses at the start");
+    assert!((19..=20).contains(&last), "{last} pulses at the end");
+}
+
+#[test]
+fn breakpoints_are_interpolated() {
+    let contour = F0Contour::Breakpoints(vec![(0.25, 100.0), (0.75, 200.0)]);
+    assert_eq!(contour.value_at(0.0, 1.0), Some(100.0));
+    assert_eq!(contour.value_at(0.5, 1.0), Some(150.0));
+    assert_eq!(contour.value_at(1.0, 1.0), Some(200.0));
+    assert_eq!(F0Contour::Breakpoints(vec![]).value_at(0.5, 1.0), None);
+}
+
+#[test]
+fn exponential_ramp_is_linear_in_octaves() {
+    let contour = F0Contour::Exponential {
+        start: 100.0,
+        end: 400.0,
+    };
+    let mid = contour.value_at(0.5, 1.0).unwrap();
+    assert!((mid - 200.0).abs() < 1E-9);
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use common::{rng, SAMPLE_RATE};
+use klatt::{generate_sound, FrameParms, FrameSwitchPolicy, MainParms};
+
+fn m_parms(frame_switch_policy: FrameSwitchPolicy) -> MainParms {
+    MainParms {
+        frame_switch_policy,
+        ..common::m_parms()
+    }
+}
+
+fn f_params(gain_db: f64) -> FrameParms {
+    FrameParms {
+        gain_db,
+        ..common::f_params(100.0)
+    }
+}
+
+/// Renders a voiced frame followed by a silent one, with a period length that does not divide the frame length.
+fn render(frame_switch_policy: FrameSwitchPolicy) -> Vec<f64> {
+    let mut voiced = f_params(0.0);
+    voiced.f0 = 97.0;
+    let frames = vec![voiced, f_params(-99.0)];
+    generate_sound(&m_parms(frame_switch_policy), &frames, rng()).unwrap()
+}
+
+#[test]
+fn period_synchronous_switch_is_late() {
+    let sound = render(FrameSwitchPolicy::PeriodSynchronous);
+    assert!(
+        sound[SAMPLE_RATE] != 0.0,
+        "The previous frame should still be active at the frame boundary."
+    );
+}
+
+#[test]
+fn sample_accurate_switch_is_exact() {
+    let sound = render(FrameSwitchPolicy::SampleAccurate {
+        crossfade_length: 0,
+    });
+    assert!(sound[SAMPLE_RATE - 1] != 0.0);
+    assert!(
+        sound[SAMPLE_RATE..].iter().all(|s| *s == 0.0),
+        "The new frame should be active from the first sample of the frame."
+    );
+}
+
+#[test]
+fn sample_accurate_switch_crossfades() {
+    let crossfade_length = 441;
+    let sound = render(FrameSwitchPolicy::SampleAccurate { crossfade_length });
+    let boundary = &sound[SAMPLE_RATE..];
+    assert!(boundary[..crossfade_length - 1].iter().any(|s| *s != 0.0));
+    assert!(
+        boundary[crossfade_length..].iter().all(|s| *s == 0.0),
+        "The crossfade should be finished after {crossfade_length} samples."
+    );
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use common::{f_params, m_parms, SAMPLE_RATE};
+use klatt::{
+    evaluate_frequency_response, get_vocal_tract_frequency_response,
+    get_vocal_tract_transfer_function_coefficients, FrequencyGrid,
+};
+
+#[test]
+fn linear_grid_includes_both_ends() {
+    let grid = FrequencyGrid::Linear {
+        start: 0.0,
+        end: 1000.0,
+        points: 5,
+    };
+    assert_eq!(
+        grid.frequencies().unwrap(),
+        vec![0.0, 250.0, 500.0, 750.0, 1000.0]
+    );
+}
+
+#[test]
+fn logarithmic_grid_has_constant_ratio() {
+    let grid = FrequencyGrid::Logarithmic {
+        start: 10.0,
+        end: 10000.0,
+        points: 4,
+    };
+    let frequencies = grid.frequencies().unwrap();
+    for (f, expected) in frequencies.iter().zip([10.0, 100.0, 1000.0, 10000.0]) {
+        assert!((f - expected).abs() < 1E-9 * expected);
+    }
+    let invalid = FrequencyGrid::Logarithmic {
+        start: 0.0,
+        end: 10000.0,
+        points: 4,
+    };
+    assert!(invalid.frequencies().is_err());
+}
+
+#[test]
+fn dc_response_matches_evaluation_at_one() {
+    let trans =
+        get_vocal_tract_transfer_function_coefficients(&m_parms(), &f_params(247.0)).unwrap();
+    let h = trans.evaluate(1.0);
+    let response = evaluate_frequency_response(&trans, SAMPLE_RATE, &[0.0]).unwrap();
+    // Both evaluations sum the coefficients in a different order, which matters near DC.
+    assert!((response[0].magnitude_db - 20.0 * h.abs().log10()).abs() < 1E-5);
+    assert!(
+        response[0].phase.abs() < 1E-9
+            || (response[0].phase.abs() - std::f64::consts::PI).abs() < 1E-9
+    );
+}
+
+#[test]
+fn formants_are_peaks_of_the_magnitude() {
+    let f_parms = f_params(247.0);
+    for (&f, &bw) in f_parms.oral_formant_freq[..3]
+        .iter()
+        .zip(&f_parms.oral_formant_bw)
+    {
+        let response =
+            get_vocal_tract_frequency_response(&m_parms(), &f_parms, &[f - bw, f, f + bw]).unwrap();
+        assert!(response[1].magnitude_db > response[0].magnitude_db);
+        assert!(response[1].magnitude_db > response[2].magnitude_db);
+    }
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use common::{m_parms, nasal_f_params, SAMPLE_RATE};
+use klatt::{
+    get_vocal_tract_second_order_sections, get_vocal_tract_transfer_function_coefficients,
+    impulse_response, step_response, SecondOrderSection, VocalTractSections,
+};
+
+/// Filters a signal with a second-order section.
+fn filter(section: &SecondOrderSection, x: &[f64]) -> Vec<f64> {
+    let (mut x1, mut x2, mut y1, mut y2) = (0.0, 0.0, 0.0, 0.0);
+    x.iter()
+        .map(|&x0| {
+            let y0 = section.b0 * x0 + section.b1 * x1 + section.b2 * x2
+                - section.a1 * y1
+                - section.a2 * y2;
+            (x2, x1, y2, y1) = (x1, x0, y1, y0);
+            y0
+        })
+        .collect()
+}
+
+/// Filters a signal with the vocal tract sections.
+fn filter_sections(sections: &VocalTractSections, x: &[f64]) -> Vec<f64> {
+    let series = |sections: &[SecondOrderSection], x: Vec<f64>| {
+        sections.iter().fold(x, |x, s| filter(s, &x))
+    };
+    let source = series(&sections.source, x.to_vec());
+    let mut branches = series(&sections.cascade, source.clone());
+    for section in &sections.parallel {
+        for (b, p) in branches.iter_mut().zip(filter(section, &source)) {
+            *b += p;
+        }
+    }
+    series(&sections.output, branches)
+}
+
+#[test]
+fn impulse_response_matches_second_order_sections() {
+    let f_parms = nasal_f_params();
+    let n = 2000;
+    let response = impulse_response(&m_parms(), &f_parms, n).unwrap();
+    let sections = get_vocal_tract_second_order_sections(&m_parms(), &f_parms).unwrap();
+    let mut impulse = vec![0.0; n];
+    impulse[0] = 1.0;
+    let expected = filter_sections(&sections, &impulse);
+    let peak = response.iter().fold(0.0_f64, |m, v| m.max(v.abs()));
+    for (i, (r, e)) in response.iter().zip(&expected).enumerate() {
+        assert!((r - e).abs() < 1E-9 * peak, "Sample {i}: {r} != {e}.");
+    }
+}
+
+#[test]
+fn step_response_is_integrated_impulse_response() {
+    let f_parms = nasal_f_params();
+    let n = SAMPLE_RATE / 10;
+    let step = step_response(&m_parms(), &f_parms, n).unwrap();
+    let impulse = impulse_response(&m_parms(), &f_parms, n).unwrap();
+    let mut sum = 0.0;
+    for (s, h) in step.iter().zip(&impulse) {
+        sum += h;
+        assert!((s - sum).abs() < 1E-9);
+    }
+    // settled at the DC gain, which can only be computed roughly from the expanded polynomials
+    assert!((step[n - 1] - step[n - 2]).abs() < 1E-9);
+    let trans = get_vocal_tract_transfer_function_coefficients(&m_parms(), &f_parms).unwrap();
+    let dc_gain = trans.evaluate(1.0);
+    assert!((step[n - 1] - dc_gain).abs() < 1E-2 * dc_gain.abs());
+}
+
+#[test]
+fn narrow_bandwidth_decays_slowly() {
+    let mut f_parms = nasal_f_params();
+    f_parms.parallel_enabled = false;
+    f_parms.oral_formant_bw[0] = 0.01;
+    let response = impulse_response(&m_parms(), &f_parms, SAMPLE_RATE).unwrap();
+    assert!(response.iter().all(|v| v.is_finite()));
+    let tail = response[SAMPLE_RATE - 1000..]
+        .iter()
+        .fold(0.0_f64, |m, v| m.max(v.abs()));
+    assert!(
+        tail > 1E-3,
+        "A formant with a bandwidth of 0.01 Hz should still ring after 1 s."
+    );
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use common::{f_params, rng};
+use klatt::{generate_sound, impulse_response, inverse_filter, FrameSwitchPolicy, MainParms};
+
+fn m_parms() -> MainParms {
+    MainParms {
+        frame_switch_policy: FrameSwitchPolicy::SampleAccurate {
+            crossfade_length: 0,
+        },
+        ..MainParms::default()
+    }
+}
+
+const F0: f64 = 100.0;
+const PERIOD_LENGTH: usize = 441;
+/// Open phase of the natural glottal source, 0.7 * `PERIOD_LENGTH`, rounded up.
+const OPEN_PHASE_LENGTH: usize = 309;
+
+/// Returns the maximum magnitude of the closed phases, relative to the maximum magnitude.
+fn get_closed_phase_level(source: &[f64]) -> f64 {
+    let max = source.iter().fold(0.0_f64, |max, x| max.max(x.abs()));
+    let closed = source
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| i % PERIOD_LENGTH > OPEN_PHASE_LENGTH)
+        .fold(0.0_f64, |max, (_, x)| max.max(x.abs()));
+    closed / max
+}
+
+#[test]
+fn impulse_response_is_inverted() {
+    let mut f_parms = f_params(F0);
+    f_parms.gain_db = -6.0;
+    f_parms.cascade_voicing_db = -3.0;
+    f_parms.nasal_formant_freq = 270.0;
+    f_parms.nasal_formant_bw = 100.0;
+    f_parms.nasal_antiformant_freq = 450.0;
+    f_parms.nasal_antiformant_bw = 100.0;
+    let response = impulse_response(&m_parms(), &f_parms, 2000).unwrap();
+    let source = inverse_filter(&m_parms(), &[f_parms], &response).unwrap();
+    assert_eq!(source.len(), response.len());
+    // the inverse filter amplifies the rounding errors at high frequencies
+    assert!((source[0] - 1.0).abs() < 1E-6);
+    assert!(source[1..].iter().all(|x| x.abs() < 1E-6));
+}
+
+#[test]
+fn glottal_source_is_recovered() {
+    let sound = generate_sound(&m_parms(), &vec![f_params(F0)], rng()).unwrap();
+    let source = inverse_filter(&m_parms(), &[f_params(F0)], &sound).unwrap();
+    assert!(get_closed_phase_level(&source) < 1E-5);
+    // the source is periodic
+    for i in 0..PERIOD_LENGTH {
+        assert!((source[PERIOD_LENGTH + i] - source[10 * PERIOD_LENGTH + i]).abs() < 1E-5);
+    }
+}
+
+#[test]
+fn frames_are_switched_at_frame_start() {
+    let mut first = f_params(F0);
+    first.duration = 0.5;
+    let mut second = f_params(F0);
+    second.duration = 0.5;
+    second.oral_formant_freq = vec![300.0, 2200.0, 3000.0, 3500.0, 4500.0, 5000.0];
+    second.gain_db = 6.0;
+    let frames = vec![first, second];
+    let sound = generate_sound(&m_parms(), &frames, rng()).unwrap();
+    let source = inverse_filter(&m_parms(), &frames, &sound).unwrap();
+    assert!(get_closed_phase_level(&source) < 1E-5);
+    // the source is the same in both frames, except for the transition
+    for i in 0..PERIOD_LENGTH {
+        assert!((source[10 * PERIOD_LENGTH + i] - source[60 * PERIOD_LENGTH + i]).abs() < 1E-5);
+    }
+    // mismatching frames do not recover the source
+    let mismatched = inverse_filter(&m_parms(), &frames[..1], &sound).unwrap();
+    assert!((mismatched[60 * PERIOD_LENGTH..61 * PERIOD_LENGTH].iter())
+        .zip(&source[60 * PERIOD_LENGTH..])
+        .any(|(x1, x2)| (x1 - x2).abs() > 0.1));
+}
+
+#[test]
+fn missing_frames_are_rejected() {
+    assert!(inverse_filter(&m_parms(), &[], &[0.0; 10]).is_err());
+}
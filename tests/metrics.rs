@@ -0,0 +1,90 @@
+mod common;
+
+use common::{f_params, rng, SAMPLE_RATE};
+use klatt::{
+    compare_renders, f0_rmse, generate_sound, log_spectral_distance, mel_cepstral_distortion,
+    FrameParms, MainParms, MetricsParms,
+};
+
+fn generate(mut f_parms: FrameParms) -> Vec<f64> {
+    f_parms.duration = 0.3;
+    generate_sound(&MainParms::default(), &vec![f_parms], rng()).unwrap()
+}
+
+#[test]
+fn identical_renders_have_no_distance() {
+    let sound = generate(f_params(120.0));
+    let metrics = compare_renders(&sound, &sound, SAMPLE_RATE, &MetricsParms::default()).unwrap();
+    assert_eq!(metrics.frame_count, (sound.len() - 1024) / 256 + 1);
+    assert!(metrics.log_spectral_distance.abs() < 1E-12);
+    assert!(metrics.mel_cepstral_distortion.abs() < 1E-12);
+    assert!(metrics.f0_rmse.abs() < 1E-12);
+    assert!(metrics.voicing_error_rate.abs() < 1E-12);
+}
+
+#[test]
+fn level_difference_is_ignored_by_mcd() {
+    let reference = generate(f_params(120.0));
+    let louder: Vec<f64> = reference.iter().map(|x| 2.0 * x).collect();
+    // the floor must be below the highest frequencies of the render
+    let mt_parms = MetricsParms {
+        floor_db: -300.0,
+        ..MetricsParms::default()
+    };
+    let lsd = log_spectral_distance(&reference, &louder, &mt_parms).unwrap();
+    assert!((lsd - 20.0 * 2.0_f64.log10()).abs() < 1E-6, "{lsd} dB");
+    let mcd = mel_cepstral_distortion(&reference, &louder, SAMPLE_RATE, &mt_parms).unwrap();
+    assert!(mcd < 1E-6, "{mcd} dB");
+}
+
+#[test]
+fn metrics_grow_with_formant_changes() {
+    let reference = generate(f_params(120.0));
+    let mt_parms = MetricsParms::default();
+    let mut previous = 0.0;
+    for factor in [1.02, 1.1, 1.3] {
+        let mut f_parms = f_params(120.0);
+        f_parms.oral_formant_freq[0] *= factor;
+        f_parms.oral_formant_freq[1] *= factor;
+        let test = generate(f_parms);
+        let metrics = compare_renders(&reference, &test, SAMPLE_RATE, &mt_parms).unwrap();
+        assert!(metrics.mel_cepstral_distortion > previous);
+        previous = metrics.mel_cepstral_distortion;
+        // the pitch is not affected
+        assert!(metrics.f0_rmse < 0.5, "{} Hz", metrics.f0_rmse);
+        assert!(metrics.voicing_error_rate.abs() < 1E-12);
+    }
+    assert!(previous > 1.0, "{previous} dB");
+}
+
+#[test]
+fn f0_difference_is_measured() {
+    let reference = generate(f_params(120.0));
+    let test = generate(f_params(126.0));
+    let rmse = f0_rmse(&reference, &test, SAMPLE_RATE, &MetricsParms::default()).unwrap();
+    assert!((rmse - 6.0).abs() < 0.5, "{rmse} Hz");
+
+    let mut silent = f_params(0.0);
+    silent.cascade_voicing_db = -99.0;
+    let metrics = compare_renders(
+        &reference,
+        &generate(silent),
+        SAMPLE_RATE,
+        &MetricsParms::default(),
+    )
+    .unwrap();
+    assert!(metrics.f0_rmse.is_nan());
+    assert!(metrics.voicing_error_rate > 0.9);
+}
+
+#[test]
+fn invalid_parameters_are_rejected() {
+    let mt_parms = MetricsParms::default();
+    assert!(compare_renders(&[0.0; 100], &[0.0; 2000], SAMPLE_RATE, &mt_parms).is_err());
+    assert!(mel_cepstral_distortion(&[0.0; 2000], &[0.0; 2000], 0, &mt_parms).is_err());
+    let mt_parms = MetricsParms {
+        cepstral_order: 40,
+        ..MetricsParms::default()
+    };
+    assert!(mel_cepstral_distortion(&[0.0; 2000], &[0.0; 2000], SAMPLE_RATE, &mt_parms).is_err());
+}
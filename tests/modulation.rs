@@ -0,0 +1,101 @@
+mod common;
+
+use common::{f_params, m_parms, rng, XorShiftRng, SAMPLE_RATE};
+use klatt::{generate_sound, FrameParms};
+
+fn render(f_parms: FrameParms) -> Vec<f64> {
+    generate_sound(&m_parms(), &vec![f_parms], rng()).unwrap()
+}
+
+/// A frame that outputs the bare glottal pulses, without any formants or noise.
+fn pulse_params() -> FrameParms {
+    FrameParms {
+        oral_formant_freq: vec![],
+        oral_formant_bw: vec![],
+        ..f_params(100.0)
+    }
+}
+
+/// Returns the F0 of each period, from the distance between successive glottal pulses.
+fn get_period_f0(signal: &[f64]) -> Vec<f64> {
+    let threshold = signal.iter().fold(0.0_f64, |m, s| m.max(s.abs())) / 4.0;
+    let onsets: Vec<usize> = (1..signal.len())
+        .filter(|i| signal[i - 1] <= threshold && signal[*i] > threshold)
+        .collect();
+    onsets
+        .windows(2)
+        .map(|w| SAMPLE_RATE as f64 / (w[1] - w[0]) as f64)
+        .collect()
+}
+
+/// Returns the RMS level of consecutive 10 ms blocks.
+fn get_rms_envelope(signal: &[f64]) -> Vec<f64> {
+    signal
+        .chunks_exact(SAMPLE_RATE / 100)
+        .map(|block| (block.iter().map(|s| s * s).sum::<f64>() / block.len() as f64).sqrt())
+        .collect()
+}
+
+#[test]
+fn vibrato_modulates_f0_at_its_rate() {
+    let f0 = get_period_f0(&render(FrameParms {
+        vibrato_rate: 5.0,
+        vibrato_depth: 2.0,
+        ..pulse_params()
+    }));
+    let (min, max) = f0.iter().fold((f64::INFINITY, 0.0_f64), |(min, max), f| {
+        (min.min(*f), max.max(*f))
+    });
+    // the vibrato is a sine, so it starts at the center frequency
+    assert!((f0[0] - 100.0).abs() < 0.5, "{} Hz", f0[0]);
+    let deviation = 2.0_f64.powf(2.0 / 12.0);
+    assert!((max / (100.0 * deviation) - 1.0).abs() < 0.01, "{max} Hz");
+    assert!((min * deviation / 100.0 - 1.0).abs() < 0.01, "{min} Hz");
+    // the F0 crosses its center twice per vibrato cycle
+    let crossings = f0
+        .windows(2)
+        .filter(|w| (w[0] > 100.0) != (w[1] > 100.0))
+        .count();
+    assert!((9..=11).contains(&crossings), "{crossings} crossings");
+}
+
+#[test]
+fn tremolo_modulates_the_envelope() {
+    let plain = get_rms_envelope(&render(f_params(200.0)));
+    let envelope = get_rms_envelope(&render(FrameParms {
+        tremolo_rate: 4.0,
+        tremolo_depth: 0.5,
+        ..f_params(200.0)
+    }));
+    // the first maximum of the tremolo is at 62.5 ms and the first minimum at 187.5 ms
+    let ratio = (envelope[6] / plain[6]) / (envelope[18] / plain[18]);
+    assert!((ratio - 3.0).abs() < 0.3, "{ratio}");
+    // the tremolo repeats after 250 ms
+    assert!((envelope[6] / envelope[31] - 1.0).abs() < 0.05);
+}
+
+#[test]
+fn drift_is_deterministic() {
+    let f_parms = FrameParms {
+        drift_level: 1.0,
+        ..pulse_params()
+    };
+    let drift = render(f_parms.clone());
+    assert!(drift == render(f_parms.clone()));
+    assert!(drift != render(pulse_params()));
+    let other_seed = generate_sound(&m_parms(), &vec![f_parms], XorShiftRng(1)).unwrap();
+    assert!(drift != other_seed);
+}
+
+#[test]
+fn zero_levels_leave_the_output_unchanged() {
+    let f_parms = FrameParms {
+        vibrato_rate: 5.0,
+        vibrato_depth: 0.0,
+        tremolo_rate: 4.0,
+        tremolo_depth: 0.0,
+        drift_level: 0.0,
+        ..f_params(100.0)
+    };
+    assert!(render(f_parms) == render(f_params(100.0)));
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use common::{f_params, rng, SAMPLE_RATE};
+use klatt::{
+    fit_frame_parameters, generate_sound, get_signal_spectrum, get_spectral_distance,
+    get_vocal_tract_frequency_response, FrameParms, FrequencyGrid, FrequencyResponsePoint,
+    MainParms, OptimizerParms, SpectrumModel,
+};
+
+fn log_grid() -> Vec<f64> {
+    FrequencyGrid::Logarithmic {
+        start: 100.0,
+        end: 5000.0,
+        points: 200,
+    }
+    .frequencies()
+    .unwrap()
+}
+
+/// Returns the frame parameters with shifted formants, as the start of the search.
+fn perturbed(f_parms: &FrameParms) -> FrameParms {
+    let mut start = f_parms.clone();
+    for (i, (f, bw)) in start
+        .oral_formant_freq
+        .iter_mut()
+        .zip(&mut start.oral_formant_bw)
+        .take(4)
+        .enumerate()
+    {
+        *f *= if i % 2 == 0 { 1.05 } else { 0.96 };
+        *bw *= 1.5;
+    }
+    start
+}
+
+#[test]
+fn sinusoid_spectrum_is_scaled_to_amplitude() {
+    let samples: Vec<f64> = (0..4410)
+        .map(|i| 0.5 * (2.0 * std::f64::consts::PI * 1000.0 * f64::from(i) / 44100.0).sin())
+        .collect();
+    let spectrum = get_signal_spectrum(&samples, SAMPLE_RATE, &[1000.0, 3000.0]).unwrap();
+    assert!((spectrum[0].magnitude_db - 20.0 * 0.5_f64.log10()).abs() < 0.01);
+    assert!(spectrum[1].magnitude_db < -60.0);
+    assert!(get_signal_spectrum(&[], SAMPLE_RATE, &[1000.0]).is_err());
+}
+
+#[test]
+fn vocal_tract_formants_are_recovered() {
+    let f_parms = f_params(100.0);
+    let target =
+        get_vocal_tract_frequency_response(&MainParms::default(), &f_parms, &log_grid()).unwrap();
+    let start = perturbed(&f_parms);
+    let o_parms = OptimizerParms {
+        formant_count: 4,
+        ..OptimizerParms::default()
+    };
+    let initial = get_spectral_distance(
+        &MainParms::default(),
+        &start,
+        &target,
+        SpectrumModel::VocalTract,
+    )
+    .unwrap();
+    let result = fit_frame_parameters(&MainParms::default(), &start, &target, &o_parms).unwrap();
+    assert!(result.distance < 0.05 * initial, "{} dB", result.distance);
+    for (estimate, f) in result.f_parms.oral_formant_freq[..4]
+        .iter()
+        .zip(&f_parms.oral_formant_freq)
+    {
+        assert!(
+            (estimate - f).abs() < 0.01 * f,
+            "{estimate} Hz instead of {f} Hz"
+        );
+    }
+    // F5 and F6 are not adjusted
+    assert_eq!(
+        result.f_parms.oral_formant_freq[4..],
+        f_parms.oral_formant_freq[4..]
+    );
+}
+
+#[test]
+fn level_offset_is_returned() {
+    let f_parms = f_params(100.0);
+    let target: Vec<FrequencyResponsePoint> =
+        get_vocal_tract_frequency_response(&MainParms::default(), &f_parms, &log_grid())
+            .unwrap()
+            .into_iter()
+            .map(|point| FrequencyResponsePoint {
+                magnitude_db: point.magnitude_db + 6.0,
+                ..point
+            })
+            .collect();
+    let result = fit_frame_parameters(
+        &MainParms::default(),
+        &f_parms,
+        &target,
+        &OptimizerParms::default(),
+    )
+    .unwrap();
+    assert!(result.distance < 1E-3);
+    assert!((result.level_offset_db + 6.0).abs() < 1E-3);
+}
+
+#[test]
+fn formants_are_fitted_to_rendered_audio() {
+    let f0 = 100.0;
+    let f_parms = f_params(f0);
+    let sound = generate_sound(&MainParms::default(), &vec![f_parms.clone()], rng()).unwrap();
+    // a segment of 10 periods from the middle of the sound
+    let segment = &sound[22050..22050 + 4410];
+    let harmonics: Vec<f64> = (1..50).map(|k| f64::from(k) * f0).collect();
+    let target = get_signal_spectrum(segment, SAMPLE_RATE, &harmonics).unwrap();
+    let start = perturbed(&f_parms);
+    let o_parms = OptimizerParms {
+        formant_count: 4,
+        spectrum_model: SpectrumModel::System,
+        ..OptimizerParms::default()
+    };
+    let initial = get_spectral_distance(
+        &MainParms::default(),
+        &start,
+        &target,
+        SpectrumModel::System,
+    )
+    .unwrap();
+    let result = fit_frame_parameters(&MainParms::default(), &start, &target, &o_parms).unwrap();
+    assert!(result.distance < 0.2 * initial, "{} dB", result.distance);
+    for (estimate, f) in result.f_parms.oral_formant_freq[..2]
+        .iter()
+        .zip(&f_parms.oral_formant_freq)
+    {
+        assert!(
+            (estimate - f).abs() < 0.03 * f,
+            "{estimate} Hz instead of {f} Hz"
+        );
+    }
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use common::{f_params, m_parms};
+use klatt::{get_vocal_tract_frequency_response, match_parallel_formant_levels, FrameParms};
+
+/// Returns the magnitude in dB of one branch at the specified frequencies.
+fn branch_levels(f_parms: &FrameParms, cascade: bool, frequencies: &[f64]) -> Vec<f64> {
+    let mut f_parms = f_parms.clone();
+    f_parms.cascade_enabled = cascade;
+    f_parms.parallel_enabled = !cascade;
+    f_parms.cascade_voicing_db = 0.0;
+    f_parms.parallel_voicing_db = 0.0;
+    get_vocal_tract_frequency_response(&m_parms(), &f_parms, frequencies)
+        .unwrap()
+        .iter()
+        .map(|point| point.magnitude_db)
+        .collect()
+}
+
+#[test]
+fn parallel_branch_matches_cascade_at_formants() {
+    let f_parms = match_parallel_formant_levels(&m_parms(), &f_params(247.0)).unwrap();
+    let frequencies = f_parms.oral_formant_freq.clone();
+    let cascade = branch_levels(&f_parms, true, &frequencies);
+    let parallel = branch_levels(&f_parms, false, &frequencies);
+    // F5 and F6 are dominated by the other parallel formants and muted
+    assert!(f_parms.oral_formant_db[4..].iter().all(|db| *db <= -99.0));
+    for (c, p) in cascade[..4].iter().zip(&parallel) {
+        // the expanded polynomials are not evaluated exactly near the formants
+        assert!((c - p).abs() < 0.05, "cascade {c} dB, parallel {p} dB");
+    }
+}
+
+#[test]
+fn nasal_formant_level_is_matched() {
+    let mut f_parms = f_params(247.0);
+    f_parms.nasal_formant_freq = 270.0;
+    f_parms.nasal_formant_bw = 100.0;
+    f_parms.nasal_antiformant_freq = 450.0;
+    f_parms.nasal_antiformant_bw = 100.0;
+    let f_parms = match_parallel_formant_levels(&m_parms(), &f_parms).unwrap();
+    assert!(f_parms.nasal_formant_db > -99.0);
+    let mut frequencies = f_parms.oral_formant_freq.clone();
+    frequencies.push(f_parms.nasal_formant_freq);
+    let cascade = branch_levels(&f_parms, true, &frequencies);
+    let parallel = branch_levels(&f_parms, false, &frequencies);
+    for (i, (c, p)) in cascade.iter().zip(&parallel).enumerate() {
+        if f_parms
+            .oral_formant_db
+            .get(i)
+            .is_some_and(|db| *db <= -99.0)
+        {
+            assert!(p > c);
+            continue;
+        }
+        assert!((c - p).abs() < 0.05, "cascade {c} dB, parallel {p} dB");
+    }
+}
+
+#[test]
+fn disabled_formant_is_muted() {
+    let mut f_parms = f_params(247.0);
+    f_parms.oral_formant_freq[3] = f64::NAN;
+    let f_parms = match_parallel_formant_levels(&m_parms(), &f_parms).unwrap();
+    assert_eq!(f_parms.oral_formant_db.len(), 6);
+    assert!((f_parms.oral_formant_db[3] - -99.0).abs() < f64::EPSILON);
+    assert!(f_parms.oral_formant_db[..3].iter().all(|db| *db > -99.0));
+}
+
+#[test]
+fn other_parameters_are_preserved() {
+    let original = f_params(247.0);
+    let f_parms = match_parallel_formant_levels(&m_parms(), &original).unwrap();
+    assert_eq!(f_parms.oral_formant_freq, original.oral_formant_freq);
+    assert!(!f_parms.parallel_enabled);
+    assert!((f_parms.parallel_voicing_db - original.parallel_voicing_db).abs() < f64::EPSILON);
+}
+
+#[test]
+fn invalid_nasal_bandwidth_is_rejected() {
+    for bw in [f64::NAN, -100.0] {
+        let mut f_parms = f_params(247.0);
+        f_parms.nasal_formant_freq = 270.0;
+        f_parms.nasal_formant_bw = bw;
+        assert!(match_parallel_formant_levels(&m_parms(), &f_parms).is_err());
+    }
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use common::{f_params, rng};
+use klatt::{
+    decode_alaw, decode_mulaw, encode_alaw, encode_mulaw, power_spectrum, to_alaw, to_i16, to_i24,
+    to_i32, to_mulaw, Dither, Generator, MainParms, MuLaw, Quantizer, Window,
+};
+
+fn sine(length: usize, amplitude: f64) -> Vec<f64> {
+    (0..length)
+        .map(|i| amplitude * (i as f64 * 0.0123).sin())
+        .collect()
+}
+
+#[test]
+fn samples_are_rounded_and_clipped() {
+    let samples = [0.0, 0.5, -0.5, 1.0, -1.0, 2.0, f64::NAN, 1.4 / 32768.0];
+    assert_eq!(
+        to_i16(&samples, Dither::None, rng()),
+        vec![0, 16384, -16384, 32767, -32768, 32767, 0, 1]
+    );
+    assert_eq!(
+        to_i24(&samples, Dither::None, rng())[..5],
+        [0, 4_194_304, -4_194_304, 8_388_607, -8_388_608]
+    );
+    assert_eq!(
+        to_i32(&samples, Dither::None, rng())[3..5],
+        [i32::MAX, i32::MIN]
+    );
+    assert!(Quantizer::new(40, Dither::None, rng()).is_err());
+}
+
+#[test]
+fn triangular_dither_linearizes_the_quantization() {
+    // a constant of a quarter LSB is rounded to 0 without dither
+    let samples = vec![0.25 / 32768.0; 100_000];
+    assert!(to_i16(&samples, Dither::None, rng())
+        .iter()
+        .all(|x| *x == 0));
+    let dithered = to_i16(&samples, Dither::Triangular, rng());
+    let mean = dithered.iter().map(|x| f64::from(*x)).sum::<f64>() / samples.len() as f64;
+    assert!((mean - 0.25).abs() < 0.01, "{mean}");
+    assert!(dithered.iter().all(|x| (-1..=2).contains(x)));
+}
+
+#[test]
+fn noise_shaping_moves_the_noise_to_high_frequencies() {
+    let signal = sine(4096, 0.3);
+    let get_noise_spectrum = |dither| {
+        let noise: Vec<f64> = to_i16(&signal, dither, rng())
+            .iter()
+            .zip(&signal)
+            .map(|(q, x)| f64::from(*q) - x * 32768.0)
+            .collect();
+        power_spectrum(&noise, Window::Hann, 4096).unwrap()
+    };
+    let flat = get_noise_spectrum(Dither::Triangular);
+    let shaped = get_noise_spectrum(Dither::NoiseShaped);
+    let get_band_power =
+        |power: &[f64], band: std::ops::Range<usize>| power[band].iter().sum::<f64>();
+    // the lowest eighth of the spectrum, and the highest eighth
+    assert!(get_band_power(&shaped, 1..256) < 0.1 * get_band_power(&flat, 1..256));
+    assert!(get_band_power(&shaped, 1792..2049) > 2.0 * get_band_power(&flat, 1792..2049));
+}
+
+#[test]
+fn g711_reference_values() {
+    assert_eq!(encode_mulaw(0.0), 0xff);
+    assert_eq!(encode_mulaw(1.0), 0x80);
+    assert_eq!(encode_mulaw(-1.0), 0x00);
+    assert_eq!(encode_alaw(0.0), 0xd5);
+    assert_eq!(encode_alaw(1.0), 0xaa);
+    assert_eq!(encode_alaw(-1.0), 0x2a);
+    assert!((decode_mulaw(0x80) - 32124.0 / 32768.0).abs() < 1E-12);
+    assert!((decode_alaw(0xaa) - 32256.0 / 32768.0).abs() < 1E-12);
+}
+
+#[test]
+fn g711_codes_round_trip() {
+    for code in 0..=255_u8 {
+        assert_eq!(encode_alaw(decode_alaw(code)), code);
+        // 0x7f is negative zero, which is encoded as positive zero
+        if code != 0x7f {
+            assert_eq!(encode_mulaw(decode_mulaw(code)), code);
+        }
+    }
+}
+
+#[test]
+fn g711_error_is_relative() {
+    let signal = sine(2000, 0.9);
+    for (codes, decode) in [
+        (to_mulaw(&signal), decode_mulaw as fn(u8) -> f64),
+        (to_alaw(&signal), decode_alaw),
+    ] {
+        for (code, x) in codes.iter().zip(&signal) {
+            // about 3 % of the amplitude, and a few LSB of 13 bit PCM near 0
+            assert!((decode(*code) - x).abs() <= 0.035 * x.abs() + 4.0 / 4096.0);
+        }
+    }
+}
+
+#[test]
+fn generator_streams_telephone_prompt() {
+    let m_parms = MainParms {
+        sample_rate: 8000,
+        ..MainParms::default()
+    };
+    // F5 and F6 are above the Nyquist frequency of 4000 Hz
+    let frames = [f_params(110.0), f_params(130.0)].map(|mut f_parms| {
+        f_parms.oral_formant_freq.truncate(4);
+        f_parms.oral_formant_bw.truncate(4);
+        f_parms
+    });
+    let mut generator = Generator::new(&m_parms, rng()).unwrap();
+    let mut expected = vec![0.0; 1600];
+    for (f_parms, buf) in frames.iter().zip(expected.chunks_mut(800)) {
+        generator.generate_frame(f_parms, buf).unwrap();
+    }
+    assert!(expected.iter().any(|x| x.abs() > 0.01));
+
+    let mut scratch_buf = vec![0.0; 800];
+    let mut generator = Generator::new(&m_parms, rng()).unwrap();
+    let mut codes = vec![0; 1600];
+    for (f_parms, buf) in frames.iter().zip(codes.chunks_mut(800)) {
+        generator
+            .generate_converted_frame(f_parms, &mut MuLaw, &mut scratch_buf, buf)
+            .unwrap();
+    }
+    assert_eq!(codes, to_mulaw(&expected));
+
+    let mut generator = Generator::new(&m_parms, rng()).unwrap();
+    let mut quantizer = Quantizer::new(16, Dither::None, rng()).unwrap();
+    let mut pcm: Vec<i16> = vec![0; 1600];
+    for (f_parms, buf) in frames.iter().zip(pcm.chunks_mut(800)) {
+        generator
+            .generate_converted_frame(f_parms, &mut quantizer, &mut scratch_buf, buf)
+            .unwrap();
+    }
+    assert_eq!(pcm, to_i16(&expected, Dither::None, rng()));
+
+    let mut generator = Generator::new(&m_parms, rng()).unwrap();
+    let mut codes = vec![0; 800];
+    assert!(generator
+        .generate_converted_frame(&frames[0], &mut MuLaw, &mut scratch_buf[..799], &mut codes)
+        .is_err());
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use common::{f_params, rng, SAMPLE_RATE};
+use klatt::{
+    estimate_pitch, generate_sound, track_pitch, FrameParms, GlottalSourceType, MainParms,
+    PitchParms,
+};
+
+fn m_parms(glottal_source_type: GlottalSourceType) -> MainParms {
+    MainParms {
+        glottal_source_type,
+        ..common::m_parms()
+    }
+}
+
+fn generate(glottal_source_type: GlottalSourceType, frames: &Vec<FrameParms>) -> Vec<f64> {
+    generate_sound(&m_parms(glottal_source_type), frames, rng()).unwrap()
+}
+
+#[test]
+fn constant_f0_is_estimated() {
+    for (glottal_source_type, f0) in [
+        (GlottalSourceType::Impulsive, 120.0),
+        (GlottalSourceType::Natural, 220.0),
+        (GlottalSourceType::Natural, 97.0),
+    ] {
+        let sound = generate(glottal_source_type, &vec![f_params(f0)]);
+        let pitch = track_pitch(&sound, SAMPLE_RATE, 100.0, &PitchParms::default()).unwrap();
+        assert_eq!(pitch.len(), 100);
+        for estimate in &pitch[5..95] {
+            assert!(estimate.is_voiced());
+            assert!(
+                (estimate.f0 - f0).abs() < 0.005 * f0,
+                "{} Hz instead of {f0} Hz",
+                estimate.f0
+            );
+            assert!(estimate.confidence > 0.8);
+        }
+    }
+}
+
+#[test]
+fn f0_follows_frames() {
+    let mut first = f_params(100.0);
+    first.duration = 0.5;
+    let mut second = f_params(200.0);
+    second.duration = 0.5;
+    let sound = generate(GlottalSourceType::Natural, &vec![first, second]);
+    let pitch = track_pitch(&sound, SAMPLE_RATE, 20.0, &PitchParms::default()).unwrap();
+    assert_eq!(pitch.len(), 20);
+    assert!((pitch[4].time - 0.225).abs() < 1E-4);
+    assert!((pitch[4].f0 - 100.0).abs() < 0.5);
+    assert!((pitch[15].f0 - 200.0).abs() < 1.0);
+}
+
+#[test]
+fn flutter_varies_f0() {
+    let mut f_parms = f_params(120.0);
+    f_parms.flutter_level = 0.25;
+    let sound = generate(GlottalSourceType::Natural, &vec![f_parms]);
+    let pitch = track_pitch(&sound, SAMPLE_RATE, 100.0, &PitchParms::default()).unwrap();
+    let f0: Vec<f64> = pitch[5..95].iter().map(|estimate| estimate.f0).collect();
+    let (min, max) = f0.iter().fold((f64::INFINITY, 0.0_f64), |(min, max), f| {
+        (min.min(*f), max.max(*f))
+    });
+    assert!(
+        min > 120.0 * 0.95 && max < 120.0 * 1.05,
+        "{min} .. {max} Hz"
+    );
+    assert!(max - min > 0.5, "{min} .. {max} Hz");
+}
+
+#[test]
+fn zero_f0_is_unvoiced() {
+    let mut silent = f_params(0.0);
+    silent.duration = 0.5;
+    let mut voiced = f_params(150.0);
+    voiced.duration = 0.5;
+    let sound = generate(GlottalSourceType::Impulsive, &vec![silent, voiced]);
+    let pitch = track_pitch(&sound, SAMPLE_RATE, 100.0, &PitchParms::default()).unwrap();
+    assert!(pitch[..45].iter().all(|estimate| !estimate.is_voiced()));
+    assert!(pitch[55..].iter().all(|estimate| estimate.is_voiced()));
+}
+
+#[test]
+fn noise_is_unvoiced() {
+    let mut f_parms = f_params(0.0);
+    f_parms.cascade_voicing_db = -99.0;
+    f_parms.cascade_aspiration_db = 0.0;
+    let sound = generate_sound(
+        &m_parms(GlottalSourceType::Impulsive),
+        &vec![f_parms],
+        rng(),
+    )
+    .unwrap();
+    let pitch = track_pitch(&sound, SAMPLE_RATE, 100.0, &PitchParms::default()).unwrap();
+    let voiced = pitch.iter().filter(|estimate| estimate.is_voiced()).count();
+    assert!(voiced < pitch.len() / 10, "{voiced} voiced frames");
+}
+
+#[test]
+fn invalid_parameters_are_rejected() {
+    let p_parms = PitchParms {
+        f0_min: 200.0,
+        f0_max: 100.0,
+        ..PitchParms::default()
+    };
+    assert!(estimate_pitch(&[0.0; 100], SAMPLE_RATE, 50, &p_parms).is_err());
+    assert!(track_pitch(&[0.0; 100], SAMPLE_RATE, 0.0, &PitchParms::default()).is_err());
+    assert!(track_pitch(&[0.0; 100], 0, 100.0, &PitchParms::default()).is_err());
+}
+
+#[test]
+fn tiny_f0_min_is_limited_to_the_signal() {
+    let mut f_parms = f_params(120.0);
+    f_parms.duration = 0.1;
+    let sound = generate(GlottalSourceType::Natural, &vec![f_parms]);
+    for f0_min in [1E-3, 1E-300, f64::MIN_POSITIVE] {
+        let p_parms = PitchParms {
+            f0_min,
+            ..PitchParms::default()
+        };
+        let estimate = estimate_pitch(&sound, SAMPLE_RATE, sound.len() / 2, &p_parms).unwrap();
+        assert!((estimate.f0 - 120.0).abs() < 1.0, "{} Hz", estimate.f0);
+    }
+}
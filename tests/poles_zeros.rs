@@ -0,0 +1,89 @@
+mod common;
+
+use common::{f_params, m_parms, SAMPLE_RATE};
+use klatt::{
+    get_poles_and_zeros, get_transfer_function_from_poles_and_zeros,
+    get_vocal_tract_poles_and_zeros, PolesAndZeros, Resonance,
+};
+
+/// Asserts that a resonance with the specified frequency and bandwidth is in the list.
+fn assert_contains(resonances: &[Resonance], frequency: f64, bandwidth: f64) {
+    // The roots of the expanded polynomials are sensitive to rounding errors of the coefficients,
+    // in particular with closely spaced formants.
+    assert!(
+        resonances
+            .iter()
+            .any(|r| (r.frequency - frequency).abs() < 0.5
+                && (r.bandwidth - bandwidth).abs() < 0.5),
+        "No resonance at {frequency} Hz with bandwidth {bandwidth} Hz in {resonances:?}."
+    );
+}
+
+#[test]
+fn cascade_poles_match_oral_formants() {
+    let f_parms = f_params(247.0);
+    let pz = get_vocal_tract_poles_and_zeros(&m_parms(), &f_parms).unwrap();
+    // 6 oral formants and the output low-pass filter, which has a double real pole
+    assert_eq!(pz.poles.len(), 8);
+    assert!(pz.zeros.is_empty());
+    for (&f, &bw) in f_parms
+        .oral_formant_freq
+        .iter()
+        .zip(&f_parms.oral_formant_bw)
+    {
+        assert_contains(&pz.poles, f, bw);
+    }
+    assert_contains(&pz.poles[..2], 0.0, SAMPLE_RATE as f64 / 2.0);
+    assert!(pz
+        .poles
+        .windows(2)
+        .all(|w| w[0].frequency <= w[1].frequency));
+}
+
+#[test]
+fn nasal_antiformant_is_a_zero() {
+    let mut f_parms = f_params(247.0);
+    f_parms.nasal_formant_freq = 270.0;
+    f_parms.nasal_formant_bw = 100.0;
+    f_parms.nasal_antiformant_freq = 450.0;
+    f_parms.nasal_antiformant_bw = 120.0;
+    let pz = get_vocal_tract_poles_and_zeros(&m_parms(), &f_parms).unwrap();
+    assert_eq!(pz.poles.len(), 9);
+    assert_contains(&pz.poles, 270.0, 100.0);
+    assert_eq!(pz.zeros.len(), 1);
+    assert_contains(&pz.zeros, 450.0, 120.0);
+}
+
+#[test]
+fn transfer_function_from_poles_and_zeros_round_trip() {
+    let pz = PolesAndZeros {
+        poles: vec![
+            Resonance {
+                frequency: 0.0,
+                bandwidth: 300.0,
+            },
+            Resonance {
+                frequency: 700.0,
+                bandwidth: 80.0,
+            },
+            Resonance {
+                frequency: 1200.0,
+                bandwidth: 90.0,
+            },
+        ],
+        zeros: vec![Resonance {
+            frequency: 900.0,
+            bandwidth: 150.0,
+        }],
+    };
+    let trans = get_transfer_function_from_poles_and_zeros(&pz, SAMPLE_RATE).unwrap();
+    assert_eq!(trans.denominator().degree(), 5);
+    assert_eq!(trans.numerator().degree(), 2);
+    assert_eq!(trans.denominator().coefficients()[0], 1.0);
+    let result = get_poles_and_zeros(&trans, SAMPLE_RATE).unwrap();
+    assert_eq!(result.poles.len(), 3);
+    for r in &pz.poles {
+        assert_contains(&result.poles, r.frequency, r.bandwidth);
+    }
+    assert_contains(&result.zeros, 900.0, 150.0);
+}
@@ -0,0 +1,50 @@
+use klatt::{evaluate_complex_polynomial, expand_roots, multiply_complex_polynomials, Complex};
+
+#[test]
+fn expanded_roots_evaluate_to_zero() {
+    let roots = [
+        Complex::new(0.5, 0.5),
+        Complex::new(0.5, -0.5),
+        Complex::new(-2.0, 0.0),
+    ];
+    let a = expand_roots(&roots);
+    assert_eq!(a.len(), 4);
+    assert_eq!(a[3], Complex::new(1.0, 0.0));
+    for r in roots {
+        assert!(evaluate_complex_polynomial(&a, r).abs() < 1E-12);
+    }
+    // Conjugate pairs result in real coefficients.
+    assert!(a.iter().all(|c| c.im.abs() < 1E-12));
+    assert!((a[0].re - 1.0).abs() < 1E-12); // -(r1 * r2 * r3) = 0.5 * 2
+}
+
+#[test]
+fn expanded_roots_multiply() {
+    let roots1 = [Complex::new(1.0, 2.0)];
+    let roots2 = [Complex::new(3.0, 0.0), Complex::new(0.5, -1.0)];
+    let a1 = expand_roots(&roots1);
+    let a2 = expand_roots(&roots2);
+    let product = expand_roots(&[roots1[0], roots2[0], roots2[1]]);
+    assert_eq!(product.len(), 4);
+    let x = Complex::new(0.3, -0.7);
+    let expected = evaluate_complex_polynomial(&a1, x) * evaluate_complex_polynomial(&a2, x);
+    assert!((evaluate_complex_polynomial(&product, x) - expected).abs() < 1E-12);
+    assert_eq!(expand_roots(&[]), vec![Complex::new(1.0, 0.0)]);
+    assert_eq!(evaluate_complex_polynomial(&[], x), Complex::default());
+}
+
+#[test]
+fn polynomials_multiply() {
+    // ((1 + i) + 2x) * (3 - ix) = (3 + 3i) + (7 - i)x - 2ix^2
+    let a1 = [Complex::new(1.0, 1.0), Complex::new(2.0, 0.0)];
+    let a2 = [Complex::new(3.0, 0.0), Complex::new(0.0, -1.0)];
+    assert_eq!(
+        multiply_complex_polynomials(&a1, &a2),
+        vec![
+            Complex::new(3.0, 3.0),
+            Complex::new(7.0, -1.0),
+            Complex::new(0.0, -2.0)
+        ]
+    );
+    assert!(multiply_complex_polynomials(&a1, &[]).is_empty());
+}
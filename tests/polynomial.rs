@@ -0,0 +1,84 @@
+use klatt::{Complex, Polynomial, RationalFunction};
+
+#[test]
+fn polynomial_arithmetic() {
+    let p = Polynomial::new(vec![1.0, 1.0]); // 1 + x
+    let q = Polynomial::new(vec![-1.0, 1.0]); // -1 + x
+    assert_eq!((&p * &q).coefficients(), &[-1.0, 0.0, 1.0]);
+    assert_eq!((&p + &q).coefficients(), &[0.0, 2.0]);
+    assert_eq!((p.clone() - p).coefficients(), &[0.0]);
+    assert_eq!(q.evaluate(3.0), 2.0);
+}
+
+#[test]
+fn polynomial_is_trimmed() {
+    let p = Polynomial::new(vec![1.0, 2.0, 0.0, 0.0]);
+    assert_eq!(p.degree(), 1);
+    assert!(Polynomial::new(vec![]).is_zero());
+    let q = Polynomial::new(vec![1.0, 2.0, 1E-12]);
+    assert_eq!(q.degree(), 2);
+    assert_eq!(q.trim(1E-10).degree(), 1);
+}
+
+#[test]
+fn sum_of_fractions_shares_common_denominator_factors() {
+    // 1 / ((1 - 0.5x)(1 - 0.25x)) + 1 / (1 - 0.5x)
+    let d1 = Polynomial::new(vec![1.0, -0.5]);
+    let d2 = Polynomial::new(vec![1.0, -0.25]);
+    let f1 = RationalFunction::new(Polynomial::constant(1.0), &d1 * &d2).with_epsilon(1E-10);
+    let f2 = RationalFunction::new(Polynomial::constant(1.0), d1);
+    let sum = &f1 + &f2;
+    assert_eq!(sum.denominator().degree(), 2);
+    assert_eq!(sum.epsilon(), 1E-10);
+    for x in [0.0, 0.3, -1.5] {
+        assert!((sum.evaluate(x) - f1.evaluate(x) - f2.evaluate(x)).abs() < 1E-12);
+    }
+}
+
+#[test]
+fn division_multiplies_with_reciprocal() {
+    let f = RationalFunction::new(
+        Polynomial::new(vec![1.0, 2.0]),
+        Polynomial::new(vec![3.0, -1.0]),
+    );
+    let g = RationalFunction::new(Polynomial::new(vec![0.5, 1.0]), Polynomial::constant(2.0));
+    let h = &f / &g;
+    for x in [0.0, 0.7, -2.0] {
+        assert!((h.evaluate(x) - f.evaluate(x) / g.evaluate(x)).abs() < 1E-12);
+    }
+    assert!((f.clone() / RationalFunction::constant(0.0))
+        .evaluate(0.0)
+        .is_infinite());
+}
+
+#[test]
+fn difference_of_fractions_cancels() {
+    // 1 / ((1 - 0.5x)(1 - 0.25x)) - 1 / (1 - 0.5x)
+    let d1 = Polynomial::new(vec![1.0, -0.5]);
+    let d2 = Polynomial::new(vec![1.0, -0.25]);
+    let f1 = RationalFunction::new(Polynomial::constant(1.0), &d1 * &d2).with_epsilon(1E-10);
+    let f2 = RationalFunction::new(Polynomial::constant(1.0), d1);
+    let difference = &f1 - &f2;
+    assert_eq!(difference.denominator().degree(), 2);
+    for x in [0.0, 0.3, -1.5] {
+        assert!((difference.evaluate(x) - f1.evaluate(x) + f2.evaluate(x)).abs() < 1E-12);
+    }
+    assert!((f1.clone() - f1).numerator().is_zero());
+}
+
+#[test]
+fn complex_evaluation() {
+    let p = Polynomial::new(vec![1.0, 0.0, 1.0]); // 1 + x^2
+    let i = Complex::new(0.0, 1.0);
+    assert_eq!(p.evaluate_complex(i), Complex::new(0.0, 0.0));
+    assert_eq!(
+        p.evaluate_complex(Complex::new(3.0, 0.0)).re,
+        p.evaluate(3.0)
+    );
+    // a one-pole lowpass has a gain of 1 / (1 + 0.5) at the Nyquist frequency, where z^-1 = -1
+    let lowpass =
+        RationalFunction::new(Polynomial::constant(1.0), Polynomial::new(vec![1.0, -0.5]));
+    let h = lowpass.evaluate_complex(Complex::expj(-core::f64::consts::PI));
+    assert!((h.abs() - 1.0 / 1.5).abs() < 1E-12);
+    assert!((lowpass.evaluate_complex(i) - Complex::new(0.8, 0.4)).abs() < 1E-12);
+}
@@ -0,0 +1,192 @@
+#![cfg(feature = "render")]
+mod common;
+
+use common::{f_params, rng, SAMPLE_RATE};
+use klatt::{
+    generate_sound, plot_frequency_response, render_spectrogram, GrayImage, MainParms,
+    ResponsePlotParms, SpectrogramImageParms,
+};
+
+fn get_crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffff_u32;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 0 {
+                crc >> 1
+            } else {
+                (crc >> 1) ^ 0xedb8_8320
+            };
+        }
+    }
+    !crc
+}
+
+/// Decodes a grayscale PNG with stored deflate blocks, checking the chunk CRCs.
+fn decode_png(png: &[u8]) -> GrayImage {
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    let mut pos = 8;
+    let (mut width, mut height, mut zlib) = (0, 0, Vec::new());
+    while pos < png.len() {
+        let length = u32::from_be_bytes(png[pos..pos + 4].try_into().unwrap()) as usize;
+        let chunk = &png[pos + 4..pos + 8 + length];
+        let crc = u32::from_be_bytes(png[pos + 8 + length..pos + 12 + length].try_into().unwrap());
+        assert_eq!(get_crc32(chunk), crc);
+        let data = &chunk[4..];
+        match &chunk[..4] {
+            b"IHDR" => {
+                width = u32::from_be_bytes(data[..4].try_into().unwrap()) as usize;
+                height = u32::from_be_bytes(data[4..8].try_into().unwrap()) as usize;
+                assert_eq!(&data[8..], &[8, 0, 0, 0, 0]);
+            }
+            b"IDAT" => zlib.extend_from_slice(data),
+            b"IEND" => assert!(data.is_empty()),
+            _ => panic!("unexpected chunk"),
+        }
+        pos += 12 + length;
+    }
+    let mut raw = Vec::new();
+    let mut block = 2;
+    loop {
+        let is_final = zlib[block] == 1;
+        let length = u16::from_le_bytes([zlib[block + 1], zlib[block + 2]]);
+        assert_eq!(
+            !length,
+            u16::from_le_bytes([zlib[block + 3], zlib[block + 4]])
+        );
+        raw.extend_from_slice(&zlib[block + 5..block + 5 + length as usize]);
+        block += 5 + length as usize;
+        if is_final {
+            break;
+        }
+    }
+    assert_eq!(block + 4, zlib.len());
+    let pixels = raw
+        .chunks(width + 1)
+        .flat_map(|row| {
+            assert_eq!(row[0], 0);
+            row[1..].to_vec()
+        })
+        .collect();
+    GrayImage {
+        width,
+        height,
+        pixels,
+    }
+}
+
+#[test]
+fn spectrogram_shows_formants() {
+    let f_parms = f_params(100.0);
+    let sound = generate_sound(&MainParms::default(), &vec![f_parms], rng()).unwrap();
+    let si_parms = SpectrogramImageParms {
+        max_frequency: 5000.0,
+        ..SpectrogramImageParms::default()
+    };
+    let image = render_spectrogram(&sound, SAMPLE_RATE, &si_parms).unwrap();
+    assert_eq!(image.width, (sound.len() - 256) / 64 + 1);
+    // bins of 86 Hz, up to 5000 Hz
+    assert_eq!(image.height, 59);
+    assert_eq!(image.pixels.len(), image.width * image.height);
+    let get_pixel = |frequency: f64| {
+        let bin = (frequency / 86.13).round() as usize;
+        image.pixels[(image.height - 1 - bin) * image.width + image.width / 2]
+    };
+    // F1 is dark, the valley between F2 and F3 is light
+    assert!(get_pixel(520.0) < 40);
+    assert!(get_pixel(2000.0) > get_pixel(520.0) + 60);
+}
+
+#[test]
+fn images_are_encoded() {
+    let image = GrayImage {
+        width: 300,
+        height: 250,
+        pixels: (0..300 * 250).map(|i| (i % 251) as u8).collect(),
+    };
+    let pgm = image.to_pgm();
+    assert!(pgm.starts_with(b"P5\n300 250\n255\n"));
+    assert_eq!(&pgm[15..], &image.pixels[..]);
+    // the image data needs two deflate blocks
+    assert_eq!(decode_png(&image.to_png().unwrap()), image);
+    let svg = image.to_svg().unwrap();
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains("href=\"data:image/png;base64,iVBORw0KGgo"));
+}
+
+#[test]
+fn empty_images_are_rejected() {
+    let short = render_spectrogram(&[0.0; 255], SAMPLE_RATE, &SpectrogramImageParms::default());
+    assert!(short.is_err());
+    let image = GrayImage {
+        width: 0,
+        height: 257,
+        pixels: vec![],
+    };
+    assert!(image.to_png().is_err());
+    assert!(image.to_svg().is_err());
+    let image = GrayImage {
+        width: 2,
+        height: 2,
+        pixels: vec![0; 3],
+    };
+    assert!(image.to_png().is_err());
+}
+
+#[test]
+fn silence_is_white() {
+    let image =
+        render_spectrogram(&[0.0; 1000], SAMPLE_RATE, &SpectrogramImageParms::default()).unwrap();
+    assert!(image.pixels.iter().all(|pixel| *pixel == 255));
+}
+
+#[test]
+fn frequency_response_plot_marks_formants() {
+    let mut f_parms = f_params(100.0);
+    f_parms.nasal_formant_freq = 250.0;
+    f_parms.nasal_formant_bw = 100.0;
+    let rp_parms = ResponsePlotParms {
+        max_frequency: 5000.0,
+        ..ResponsePlotParms::default()
+    };
+    let svg = plot_frequency_response(&MainParms::default(), &f_parms, &rp_parms).unwrap();
+    assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+    // F6 at 5020 Hz is outside of the plot
+    assert_eq!(svg.matches("class=\"formant\"").count(), 6);
+    assert!(svg.contains("stroke=\"blue\""));
+    assert!(!svg.contains("stroke=\"green\""));
+    // F1 at 520 Hz is at x = 520 / 5000 * 800
+    assert!(svg.contains("x1=\"83.2\""));
+    let points = svg
+        .split("points=\"")
+        .nth(1)
+        .unwrap()
+        .split('"')
+        .next()
+        .unwrap();
+    assert_eq!(points.split_whitespace().count(), 800);
+    // the maximum of the response is at the nasal formant, within 10 dB of the top of the plot
+    let (x, y) = points
+        .split_whitespace()
+        .map(|point| {
+            let (x, y) = point.split_once(',').unwrap();
+            (x.parse::<f64>().unwrap(), y.parse::<f64>().unwrap())
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap();
+    assert!((x - 40.0).abs() < 3.0 && y <= 50.0, "maximum at {x}, {y}");
+}
+
+#[test]
+fn invalid_parameters_are_rejected() {
+    let si_parms = SpectrogramImageParms {
+        dynamic_range_db: 0.0,
+        ..SpectrogramImageParms::default()
+    };
+    assert!(render_spectrogram(&[0.0; 1000], SAMPLE_RATE, &si_parms).is_err());
+    let rp_parms = ResponsePlotParms {
+        width: 1,
+        ..ResponsePlotParms::default()
+    };
+    assert!(plot_frequency_response(&MainParms::default(), &f_params(100.0), &rp_parms).is_err());
+}
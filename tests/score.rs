@@ -0,0 +1,81 @@
+mod common;
+
+use common::{m_parms, reference_f_params, rng};
+use klatt::{generate_sound, Generator, Parameter, Score};
+
+/// A diphthong from /a/ to /i/ with falling pitch.
+fn score() -> Score {
+    let mut score = Score::new(reference_f_params(), 0.5);
+    score
+        .track_mut(Parameter::F0)
+        .add_point(0.0, 130.0)
+        .add_point(0.5, 100.0);
+    score
+        .track_mut(Parameter::OralFormantFreq(0))
+        .add_point(0.1, 700.0)
+        .add_point(0.4, 300.0);
+    score
+        .track_mut(Parameter::OralFormantFreq(1))
+        .add_point(0.1, 1200.0)
+        .add_point(0.4, 2300.0);
+    score
+}
+
+#[test]
+fn render_frames() {
+    let frames = score().render_frames(200.0).unwrap();
+    assert_eq!(frames.len(), 100);
+    assert!((frames[0].f0 - 130.0).abs() < 1E-9);
+    assert!((frames[50].f0 - 115.0).abs() < 1E-9);
+    assert!((frames[50].oral_formant_freq[0] - 500.0).abs() < 1E-9);
+    assert!((frames[99].oral_formant_freq[1] - 2300.0).abs() < 1E-9);
+    // parameters without a track keep their base value
+    assert!(frames
+        .iter()
+        .all(|f| f.oral_formant_freq[2] == 2831.0 && (f.duration - 0.005).abs() < 1E-12));
+}
+
+#[test]
+fn streaming_matches_rendered_frames() {
+    let score = score();
+    let streamed = score.generate_sound(&m_parms(), 200.0, rng()).unwrap();
+    let rendered = generate_sound(&m_parms(), &score.render_frames(200.0).unwrap(), rng()).unwrap();
+    assert_eq!(streamed.len(), 22050);
+    assert!(streamed == rendered);
+}
+
+#[test]
+fn frames_can_be_reused() {
+    let mut f_parms = reference_f_params();
+    f_parms.duration = 0.25;
+    let m_parms = m_parms();
+    let mut generator = Generator::new(&m_parms, rng()).unwrap();
+    let mut streamed = vec![0.0; 22050];
+    for frame_buf in streamed.chunks_mut(11025) {
+        generator.generate_frame(&f_parms, frame_buf).unwrap();
+    }
+    let rendered = generate_sound(&m_parms, &vec![f_parms.clone(), f_parms], rng()).unwrap();
+    assert!(streamed == rendered);
+}
+
+#[test]
+fn invalid_durations_are_rejected() {
+    for duration in [-0.1, f64::NAN, f64::INFINITY] {
+        let mut f_parms = reference_f_params();
+        f_parms.duration = duration;
+        let frames = vec![reference_f_params(), f_parms];
+        assert!(generate_sound(&m_parms(), &frames, rng()).is_err());
+        let score = Score::new(reference_f_params(), duration);
+        assert!(score.generate_sound(&m_parms(), 200.0, rng()).is_err());
+    }
+}
+
+#[test]
+fn invalid_frame_rates_are_rejected() {
+    let score = score();
+    for frame_rate in [0.0, -5.0, f64::NAN, f64::INFINITY] {
+        assert!(score.frames(frame_rate).is_err());
+        assert!(score.render_frames(frame_rate).is_err());
+        assert!(score.generate_sound(&m_parms(), frame_rate, rng()).is_err());
+    }
+}
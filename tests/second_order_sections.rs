@@ -0,0 +1,59 @@
+mod common;
+
+use common::{m_parms, nasal_f_params, SAMPLE_RATE};
+use klatt::{
+    evaluate_frequency_response, get_vocal_tract_second_order_sections,
+    get_vocal_tract_transfer_function_coefficients, SecondOrderSection,
+};
+
+#[test]
+fn sections_match_transfer_function() {
+    for (cascade_enabled, parallel_enabled) in [(true, false), (false, true), (true, true)] {
+        let mut f_parms = nasal_f_params();
+        f_parms.cascade_enabled = cascade_enabled;
+        f_parms.parallel_enabled = parallel_enabled;
+        let sections = get_vocal_tract_second_order_sections(&m_parms(), &f_parms).unwrap();
+        let frequencies = [0.0, 100.0, 520.0, 1006.0, 2000.0, 4135.0, 10000.0];
+        let expected = evaluate_frequency_response(
+            &get_vocal_tract_transfer_function_coefficients(&m_parms(), &f_parms).unwrap(),
+            SAMPLE_RATE,
+            &frequencies,
+        )
+        .unwrap();
+        let actual = evaluate_frequency_response(
+            &sections.get_transfer_function_coefficients(),
+            SAMPLE_RATE,
+            &frequencies,
+        )
+        .unwrap();
+        for (e, a) in expected.iter().zip(&actual) {
+            assert!(
+                (e.magnitude_db - a.magnitude_db).abs() < 0.1,
+                "{} Hz: {} dB != {} dB",
+                e.frequency,
+                e.magnitude_db,
+                a.magnitude_db
+            );
+        }
+    }
+}
+
+#[test]
+fn section_layout() {
+    let sections = get_vocal_tract_second_order_sections(&m_parms(), &nasal_f_params()).unwrap();
+    assert_eq!(sections.source.len(), 1);
+    // nasal antiformant, nasal formant and 6 oral formants
+    assert_eq!(sections.cascade.len(), 8);
+    // nasal formant, 6 oral formants and bypass
+    assert_eq!(sections.parallel.len(), 8);
+    assert_eq!(sections.output.len(), 1);
+
+    let mut f_parms = nasal_f_params();
+    f_parms.tilt_db = 0.0;
+    f_parms.cascade_enabled = false;
+    f_parms.parallel_bypass_db = -99.0;
+    let sections = get_vocal_tract_second_order_sections(&m_parms(), &f_parms).unwrap();
+    assert!(sections.source.is_empty());
+    assert_eq!(sections.cascade, vec![SecondOrderSection::gain(0.0)]);
+    assert_eq!(sections.parallel.len(), 7);
+}
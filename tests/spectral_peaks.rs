@@ -0,0 +1,52 @@
+mod common;
+
+use common::{f_params, m_parms};
+use klatt::{find_spectral_peaks, get_vocal_tract_spectral_peaks, FrequencyResponsePoint};
+
+#[test]
+fn parabolic_peak_is_interpolated() {
+    let response: Vec<FrequencyResponsePoint> = (0..=10)
+        .map(|i| {
+            let frequency = f64::from(i);
+            FrequencyResponsePoint {
+                frequency,
+                magnitude_db: -(frequency - 5.3).powi(2),
+                phase: 0.0,
+            }
+        })
+        .collect();
+    let peaks = find_spectral_peaks(&response);
+    assert_eq!(peaks.len(), 1);
+    assert!((peaks[0].frequency - 5.3).abs() < 1E-12);
+    assert!(peaks[0].level_db.abs() < 1E-12);
+    // -3 dB at 5.3 +- sqrt(3), linearly interpolated between the grid points
+    assert!((peaks[0].bandwidth - 2.0 * 3.0_f64.sqrt()).abs() < 0.1);
+}
+
+#[test]
+fn cascade_peaks_match_formants() {
+    let f_parms = f_params(247.0);
+    let peaks = get_vocal_tract_spectral_peaks(&m_parms(), &f_parms).unwrap();
+    // F5 and F6 are too wide to form separate peaks
+    assert_eq!(peaks.len(), 4);
+    for (peak, f) in peaks.iter().zip(&f_parms.oral_formant_freq) {
+        assert!(
+            (peak.frequency - f).abs() < 10.0,
+            "{peak:?} does not match {f} Hz."
+        );
+    }
+    assert!((peaks[0].bandwidth - f_parms.oral_formant_bw[0]).abs() < 2.0);
+    assert!(peaks.windows(2).all(|w| w[0].level_db > w[1].level_db));
+}
+
+#[test]
+fn parallel_peaks_deviate_from_formants() {
+    let mut f_parms = f_params(247.0);
+    f_parms.cascade_enabled = false;
+    f_parms.parallel_enabled = true;
+    f_parms.parallel_voicing_db = 0.0;
+    let peaks = get_vocal_tract_spectral_peaks(&m_parms(), &f_parms).unwrap();
+    assert!(peaks.len() >= 4);
+    // the interaction of the parallel formants shifts the peaks by several Hz
+    assert!((peaks[2].frequency - f_parms.oral_formant_freq[2]).abs() > 5.0);
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use common::{f_params, rng, SAMPLE_RATE};
+use klatt::{
+    fft, generate_sound, get_bin_frequencies, get_signal_spectrum, ifft,
+    long_term_average_spectrum, power_spectrum, spectrogram, Complex, MainParms, SpectrumParms,
+    Window,
+};
+use std::f64::consts::PI;
+
+const SP_PARMS: SpectrumParms = SpectrumParms {
+    window: Window::Hann,
+    window_length: 2048,
+    hop_length: 441,
+    fft_size: 4096,
+};
+
+#[test]
+fn fft_matches_dft() {
+    let input: Vec<Complex> = (0..16)
+        .map(|i| Complex::new(f64::from(i).sin(), f64::from(i * i % 7)))
+        .collect();
+    let mut output = input.clone();
+    fft(&mut output).unwrap();
+    for (k, x) in output.iter().enumerate() {
+        let dft = input
+            .iter()
+            .enumerate()
+            .fold(Complex::default(), |sum, (n, v)| {
+                sum + *v * Complex::expj(-2.0 * PI * (k * n) as f64 / 16.0)
+            });
+        assert!((*x - dft).abs() < 1E-12);
+    }
+    ifft(&mut output).unwrap();
+    for (x, v) in output.iter().zip(&input) {
+        assert!((*x - *v).abs() < 1E-12);
+    }
+    assert!(fft(&mut [Complex::default(); 12]).is_err());
+}
+
+#[test]
+fn sinusoid_power_is_scaled_to_amplitude() {
+    let fft_size = 1024;
+    // the frequency of bin 100
+    let f = get_bin_frequencies(fft_size, SAMPLE_RATE)[100];
+    let segment: Vec<f64> = (0..fft_size)
+        .map(|i| 0.5 * (2.0 * PI * f * i as f64 / SAMPLE_RATE as f64).cos())
+        .collect();
+    for window in [
+        Window::Rectangular,
+        Window::Hann,
+        Window::Hamming,
+        Window::Blackman,
+    ] {
+        let power = power_spectrum(&segment, window, fft_size).unwrap();
+        assert_eq!(power.len(), fft_size / 2 + 1);
+        assert!(
+            (power[100] - 0.25).abs() < 1E-3,
+            "{window:?}: {}",
+            power[100]
+        );
+        let max = power.iter().copied().fold(0.0, f64::max);
+        assert!((max - power[100]).abs() < f64::EPSILON);
+    }
+}
+
+#[test]
+fn windows_are_symmetric() {
+    for window in [Window::Hann, Window::Hamming, Window::Blackman] {
+        let coefficients = window.coefficients(101);
+        assert!((coefficients[50] - 1.0).abs() < 1E-12);
+        for i in 0..50 {
+            assert!((coefficients[i] - coefficients[100 - i]).abs() < 1E-12);
+        }
+    }
+    assert_eq!(Window::Rectangular.coefficients(3), vec![1.0; 3]);
+}
+
+#[test]
+fn strongest_harmonics_are_near_formants() {
+    let f_parms = f_params(100.0);
+    let sound = generate_sound(&MainParms::default(), &vec![f_parms], rng()).unwrap();
+    let ltas = long_term_average_spectrum(&sound, &SP_PARMS).unwrap();
+    let frequencies = get_bin_frequencies(SP_PARMS.fft_size, SAMPLE_RATE);
+    // the harmonics closest to F1 (520 Hz) and F2 (1006 Hz) dominate their frequency ranges
+    for (min, max, harmonic) in [(150.0, 800.0, 500.0), (800.0, 2000.0, 1000.0)] {
+        let strongest = (0..ltas.len())
+            .filter(|k| (min..max).contains(&frequencies[*k]))
+            .max_by(|i, j| ltas[*i].total_cmp(&ltas[*j]))
+            .unwrap();
+        assert!(
+            (frequencies[strongest] - harmonic).abs() < 10.0,
+            "strongest harmonic at {} Hz",
+            frequencies[strongest]
+        );
+    }
+}
+
+#[test]
+fn spectrogram_follows_frames() {
+    let mut first = f_params(100.0);
+    first.duration = 0.5;
+    let mut second = first.clone();
+    second.oral_formant_freq[0] = 300.0;
+    let sound = generate_sound(&MainParms::default(), &vec![first, second], rng()).unwrap();
+    let rows = spectrogram(&sound, &SP_PARMS).unwrap();
+    assert_eq!(
+        rows.len(),
+        (sound.len() - SP_PARMS.window_length) / SP_PARMS.hop_length + 1
+    );
+    assert!(rows
+        .iter()
+        .all(|row| row.len() == SP_PARMS.fft_size / 2 + 1));
+    // the harmonic at 300 Hz is amplified by F1 in the second half
+    let bin_300 = 28; // 300 Hz is close to bin 27.9
+    let (early, late) = (&rows[10], &rows[rows.len() - 10]);
+    assert!(late[bin_300] > early[bin_300] + 6.0);
+}
+
+#[test]
+fn invalid_parameters_are_rejected() {
+    let sp_parms = SpectrumParms {
+        fft_size: 1000,
+        ..SP_PARMS
+    };
+    assert!(spectrogram(&[0.0; 5000], &sp_parms).is_err());
+    assert!(long_term_average_spectrum(&[0.0; 100], &SP_PARMS).is_err());
+    assert!(power_spectrum(&[0.0; 10], Window::Hann, 8).is_err());
+}
+
+#[test]
+fn short_segments_are_handled() {
+    // the symmetric Hann and Blackman windows of 2 samples are 0 everywhere
+    assert!(power_spectrum(&[1.0, 1.0], Window::Hann, 8).is_err());
+    assert!(power_spectrum(&[1.0, 1.0], Window::Blackman, 8).is_err());
+    let power = power_spectrum(&[1.0, 1.0], Window::Hamming, 8).unwrap();
+    assert!(power.iter().all(|p| p.is_finite()));
+    // the window of `get_signal_spectrum` is never 0 everywhere
+    for samples in [&[1.0][..], &[1.0, 1.0]] {
+        let spectrum = get_signal_spectrum(samples, SAMPLE_RATE, &[0.0, 1000.0]).unwrap();
+        assert!(spectrum.iter().all(|point| point.magnitude_db.is_finite()));
+    }
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use common::rng;
+use klatt::{
+    generate_sound, get_system_frequency_response, FrameParms, GlottalSourceType, MainParms,
+};
+use std::f64::consts::PI;
+
+const F0: f64 = 100.0;
+const PERIOD_LENGTH: usize = 441;
+
+fn m_parms(glottal_source_type: GlottalSourceType) -> MainParms {
+    MainParms {
+        glottal_source_type,
+        ..common::m_parms()
+    }
+}
+
+fn f_params() -> FrameParms {
+    FrameParms {
+        tilt_db: 5.0,
+        parallel_enabled: true,
+        parallel_voicing_db: 0.0,
+        ..common::f_params(F0)
+    }
+}
+
+/// Returns the amplitude of a harmonic of a periodic signal, multiplied by the period length.
+fn get_harmonic_level(signal: &[f64], harmonic: usize) -> f64 {
+    let periods = signal.len() / PERIOD_LENGTH;
+    let (re, im) = signal
+        .iter()
+        .enumerate()
+        .fold((0.0, 0.0), |(re, im), (i, s)| {
+            let phi = -2.0 * PI * (harmonic * i) as f64 / PERIOD_LENGTH as f64;
+            (re + s * phi.cos(), im + s * phi.sin())
+        });
+    20.0 * ((re * re + im * im).sqrt() / periods as f64).log10()
+}
+
+fn check_harmonics(glottal_source_type: GlottalSourceType) {
+    let m_parms = m_parms(glottal_source_type);
+    let f_parms = f_params();
+    let sound = generate_sound(&m_parms, &vec![f_parms.clone()], rng()).unwrap();
+    // skip the onset, analyze a whole number of periods in the steady state
+    let steady = &sound[20 * PERIOD_LENGTH..80 * PERIOD_LENGTH];
+    let frequencies: Vec<f64> = (1..=40).map(|k| k as f64 * F0).collect();
+    let response = get_system_frequency_response(&m_parms, &f_parms, &frequencies).unwrap();
+    for (k, point) in (1..=40).zip(response) {
+        let level = get_harmonic_level(steady, k);
+        assert!(
+            (level - point.magnitude_db).abs() < 0.1,
+            "Harmonic {k}: generated {level} dB, predicted {} dB.",
+            point.magnitude_db
+        );
+    }
+}
+
+#[test]
+fn impulsive_source_harmonics_match_prediction() {
+    check_harmonics(GlottalSourceType::Impulsive);
+}
+
+#[test]
+fn natural_source_harmonics_match_prediction() {
+    check_harmonics(GlottalSourceType::Natural);
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use common::{m_parms, reference_f_params, rng};
+use klatt::{
+    generate_sound, get_vocal_tract_transfer_function_coefficients, FrameParms, RationalFunction,
+};
+
+/// Evaluates a polynomial in `z^-1` on the unit circle, returns `(re, im)`.
+fn evaluate_polynomial(a: &[f64], w: f64) -> (f64, f64) {
+    a.iter().enumerate().fold((0.0, 0.0), |(re, im), (k, c)| {
+        let phi = -w * k as f64;
+        (re + c * phi.cos(), im + c * phi.sin())
+    })
+}
+
+/// Evaluates a transfer function at a frequency in Hz.
+fn evaluate(trans: &RationalFunction, f: f64) -> (f64, f64) {
+    let w = 2.0 * std::f64::consts::PI * f / 44100.0;
+    let (nr, ni) = evaluate_polynomial(trans.numerator().coefficients(), w);
+    let (dr, di) = evaluate_polynomial(trans.denominator().coefficients(), w);
+    let d = dr * dr + di * di;
+    ((nr * dr + ni * di) / d, (ni * dr - nr * di) / d)
+}
+
+/// Checks that the overall transfer function is the sum of the cascade-only and parallel-only ones.
+fn check_sum_of_branches(f_parms: &FrameParms, expected_bottom_len: usize) {
+    let m_parms = m_parms();
+    let trans = get_vocal_tract_transfer_function_coefficients(&m_parms, f_parms).unwrap();
+    assert_eq!(
+        trans.denominator().degree() + 1,
+        expected_bottom_len,
+        "Shared poles should only appear once in the denominator."
+    );
+    let mut cascade_only = f_parms.clone();
+    cascade_only.parallel_enabled = false;
+    let cascade = get_vocal_tract_transfer_function_coefficients(&m_parms, &cascade_only).unwrap();
+    let mut parallel_only = f_parms.clone();
+    parallel_only.cascade_enabled = false;
+    let parallel =
+        get_vocal_tract_transfer_function_coefficients(&m_parms, &parallel_only).unwrap();
+    for f in [
+        0.0, 100.0, 520.0, 1006.0, 1500.0, 2831.0, 4000.0, 8000.0, 15000.0,
+    ] {
+        let h = evaluate(&trans, f);
+        let hc = evaluate(&cascade, f);
+        let hp = evaluate(&parallel, f);
+        let (er, ei) = (h.0 - hc.0 - hp.0, h.1 - hc.1 - hp.1);
+        let error = (er * er + ei * ei).sqrt();
+        let level = (h.0 * h.0 + h.1 * h.1).sqrt();
+        // The coefficients of high order polynomials are badly conditioned near narrow formants,
+        // which also limits the accuracy of the branch-only reference values.
+        assert!(
+            error <= 1E-2 * level,
+            "H({f}) = {h:?} is not the sum of the branches {hc:?} + {hp:?}."
+        );
+    }
+}
+
+#[test]
+fn same_formants_in_both_branches() {
+    // 6 oral formants + the output low-pass filter, 2 poles each
+    check_sum_of_branches(&reference_f_params(), 15);
+}
+
+#[test]
+fn nasal_formant_only_in_cascade_branch() {
+    let mut f_parms = reference_f_params();
+    f_parms.nasal_formant_freq = 270.0;
+    f_parms.nasal_formant_bw = 100.0;
+    f_parms.nasal_formant_db = -99.0;
+    check_sum_of_branches(&f_parms, 17);
+}
+
+#[test]
+fn fewer_formants_in_parallel_branch() {
+    let mut f_parms = reference_f_params();
+    f_parms.oral_formant_db = vec![0.0, -8.0, -15.0, -19.0];
+    check_sum_of_branches(&f_parms, 15);
+}
+
+#[test]
+fn muted_formant_in_parallel_branch() {
+    // a level of -99 dB mutes the formant, as if it had no level at all
+    let mut f_parms = reference_f_params();
+    f_parms.oral_formant_db[5] = -99.0;
+    let mut fewer = reference_f_params();
+    fewer.oral_formant_db.truncate(5);
+    check_sum_of_branches(&f_parms, 15);
+    let trans = get_vocal_tract_transfer_function_coefficients(&m_parms(), &f_parms).unwrap();
+    let expected = get_vocal_tract_transfer_function_coefficients(&m_parms(), &fewer).unwrap();
+    assert_eq!(trans, expected);
+    let sound = generate_sound(&m_parms(), &vec![f_parms], rng()).unwrap();
+    assert!(sound == generate_sound(&m_parms(), &vec![fewer], rng()).unwrap());
+}
@@ -0,0 +1,34 @@
+mod common;
+
+use common::{f_params, m_parms, rng, SAMPLE_RATE};
+use klatt::generate_sound;
+
+#[test]
+fn voicing_resumes_at_frame_start() {
+    let frames = vec![f_params(0.0), f_params(97.0)];
+    let sound = generate_sound(&m_parms(), &frames, rng()).unwrap();
+    assert!(
+        sound[..SAMPLE_RATE].iter().all(|s| *s == 0.0),
+        "There should be no voicing while f0 is 0."
+    );
+    // the glottal pulse starts one sample into the new period
+    assert!(sound[SAMPLE_RATE] == 0.0);
+    assert!(
+        sound[SAMPLE_RATE + 1] != 0.0,
+        "Voicing should resume with a new F0 period at the start of the frame."
+    );
+}
+
+#[test]
+fn unvoiced_noise_is_not_modulated() {
+    let mut unvoiced = f_params(0.0);
+    unvoiced.cascade_voicing_db = -99.0;
+    unvoiced.cascade_aspiration_db = 0.0;
+    unvoiced.cascade_aspiration_mod = 1.0;
+    let sound = generate_sound(&m_parms(), &vec![unvoiced], rng()).unwrap();
+    let silent_run = sound.split(|s| *s != 0.0).map(<[f64]>::len).max().unwrap();
+    assert!(
+        silent_run < 10,
+        "Full modulation must not silence unvoiced aspiration: {silent_run} silent samples in a row."
+    );
+}
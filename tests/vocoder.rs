@@ -0,0 +1,154 @@
+#![cfg(feature = "analysis")]
+mod common;
+
+use common::{f_params, rng, SAMPLE_RATE};
+use klatt::{
+    analyze, generate_sound, scale_formants, shift_pitch, track_pitch, vocode, whisper,
+    AnalysisParms, F0Contour, FrameParms, MainParms, PitchParms,
+};
+
+fn original() -> Vec<f64> {
+    generate_sound(&MainParms::default(), &vec![f_params(120.0)], rng()).unwrap()
+}
+
+/// A frame with a contour that overrides its f0.
+fn contour_params(f0_contour: F0Contour) -> FrameParms {
+    FrameParms {
+        f0_contour: Some(f0_contour),
+        ..f_params(0.0)
+    }
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(f64::total_cmp);
+    values[values.len() / 2]
+}
+
+#[test]
+fn unmodified_resynthesis_keeps_pitch() {
+    let sound = original();
+    let output = vocode(
+        &sound,
+        &MainParms::default(),
+        &AnalysisParms::default(),
+        |_| {},
+        rng(),
+    )
+    .unwrap();
+    assert_eq!(output.len(), sound.len());
+    let pitch = track_pitch(&output, SAMPLE_RATE, 100.0, &PitchParms::default()).unwrap();
+    let f0 = median(pitch.iter().map(|estimate| estimate.f0).collect());
+    assert!((f0 - 120.0).abs() < 1.0, "{f0} Hz");
+}
+
+#[test]
+fn pitch_is_shifted() {
+    let output = vocode(
+        &original(),
+        &MainParms::default(),
+        &AnalysisParms::default(),
+        |frames| shift_pitch(frames, 12.0),
+        rng(),
+    )
+    .unwrap();
+    let pitch = track_pitch(&output, SAMPLE_RATE, 100.0, &PitchParms::default()).unwrap();
+    let f0 = median(pitch.iter().map(|estimate| estimate.f0).collect());
+    assert!((f0 - 240.0).abs() < 2.0, "{f0} Hz");
+}
+
+#[test]
+fn pitch_contours_are_shifted() {
+    let mut frames = vec![
+        contour_params(F0Contour::Linear {
+            start: 100.0,
+            end: 150.0,
+        }),
+        contour_params(F0Contour::Exponential {
+            start: 120.0,
+            end: 90.0,
+        }),
+        contour_params(F0Contour::Breakpoints(vec![(0.0, 110.0), (0.5, 130.0)])),
+    ];
+    shift_pitch(&mut frames, -12.0);
+    assert!(
+        frames[0].f0_contour
+            == Some(F0Contour::Linear {
+                start: 50.0,
+                end: 75.0
+            })
+    );
+    assert!(
+        frames[1].f0_contour
+            == Some(F0Contour::Exponential {
+                start: 60.0,
+                end: 45.0
+            })
+    );
+    assert!(frames[2].f0_contour == Some(F0Contour::Breakpoints(vec![(0.0, 55.0), (0.5, 65.0)])));
+}
+
+#[test]
+fn formants_are_scaled() {
+    let output = vocode(
+        &original(),
+        &MainParms::default(),
+        &AnalysisParms::default(),
+        |frames| scale_formants(frames, &MainParms::default(), 1.2),
+        rng(),
+    )
+    .unwrap();
+    let frames = analyze(&output, SAMPLE_RATE, &AnalysisParms::default()).unwrap();
+    let f1 = median(
+        frames[10..90]
+            .iter()
+            .map(|f| f.oral_formant_freq[0])
+            .collect(),
+    );
+    let f2 = median(
+        frames[10..90]
+            .iter()
+            .map(|f| f.oral_formant_freq[1])
+            .collect(),
+    );
+    assert!((f1 - 1.2 * 520.0).abs() < 0.1 * 1.2 * 520.0, "F1 {f1} Hz");
+    assert!((f2 - 1.2 * 1006.0).abs() < 0.1 * 1.2 * 1006.0, "F2 {f2} Hz");
+}
+
+#[test]
+fn high_formants_are_bypassed() {
+    let mut frames = vec![f_params(120.0)];
+    scale_formants(&mut frames, &MainParms::default(), 5.0);
+    assert!((frames[0].oral_formant_freq[0] - 2600.0).abs() < 1E-9);
+    assert!(frames[0].oral_formant_freq[5].is_nan() && frames[0].oral_formant_bw[5].is_nan());
+}
+
+#[test]
+fn whispered_speech_is_unvoiced() {
+    let sound = original();
+    let output = vocode(
+        &sound,
+        &MainParms::default(),
+        &AnalysisParms::default(),
+        whisper,
+        rng(),
+    )
+    .unwrap();
+    let pitch = track_pitch(&output, SAMPLE_RATE, 100.0, &PitchParms::default()).unwrap();
+    let voiced = pitch.iter().filter(|estimate| estimate.is_voiced()).count();
+    assert!(voiced < pitch.len() / 10, "{voiced} voiced frames");
+    let rms = |buf: &[f64]| (buf.iter().map(|x| x * x).sum::<f64>() / buf.len() as f64).sqrt();
+    assert!((rms(&output) / rms(&sound) - 1.0).abs() < 0.1);
+}
+
+#[test]
+fn frames_with_contours_are_whispered() {
+    let mut frames = vec![contour_params(F0Contour::Linear {
+        start: 100.0,
+        end: 150.0,
+    })];
+    whisper(&mut frames);
+    assert!(frames[0].f0_contour.is_none());
+    assert_eq!(frames[0].f0, 0.0);
+    assert_eq!(frames[0].cascade_voicing_db, -99.0);
+    assert_eq!(frames[0].cascade_aspiration_db, 0.0);
+}
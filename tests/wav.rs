@@ -0,0 +1,191 @@
+#![cfg(feature = "wav")]
+use hound::WavReader;
+use klatt::{decode_wav, encode_wav, WavSampleFormat, WavSpec, WavWriter};
+use std::io::Cursor;
+
+const FORMATS: [WavSampleFormat; 6] = [
+    WavSampleFormat::Pcm8,
+    WavSampleFormat::Pcm16,
+    WavSampleFormat::Pcm24,
+    WavSampleFormat::Pcm32,
+    WavSampleFormat::Float32,
+    WavSampleFormat::Float64,
+];
+
+fn spec(sample_format: WavSampleFormat) -> WavSpec {
+    WavSpec {
+        sample_rate: 44100,
+        channels: 1,
+        sample_format,
+    }
+}
+
+fn signal() -> Vec<f64> {
+    (0..1001)
+        .map(|i| 0.9 * (f64::from(i) * 0.05).sin())
+        .collect()
+}
+
+/// Returns the maximum quantization error of a format.
+fn get_step(sample_format: WavSampleFormat) -> f64 {
+    match sample_format {
+        WavSampleFormat::Pcm8 => 0.5 / 128.0,
+        WavSampleFormat::Pcm16 => 0.5 / 32768.0,
+        WavSampleFormat::Pcm24 => 0.5 / 8_388_608.0,
+        WavSampleFormat::Pcm32 => 0.5 / 2_147_483_648.0,
+        WavSampleFormat::Float32 => 1E-7,
+        WavSampleFormat::Float64 => 0.0,
+    }
+}
+
+#[test]
+fn reference_file_is_decoded_like_hound() {
+    let bytes = std::fs::read("reference.wav").unwrap();
+    let (spec, samples) = decode_wav(&bytes).unwrap();
+    assert_eq!(spec, self::spec(WavSampleFormat::Float32));
+    let mut reader = WavReader::new(Cursor::new(&bytes)).unwrap();
+    let expected: Vec<f64> = reader
+        .samples::<f32>()
+        .map(|sample| f64::from(sample.unwrap()))
+        .collect();
+    assert_eq!(samples, expected);
+}
+
+#[test]
+fn encoded_files_round_trip() {
+    let signal = signal();
+    for sample_format in FORMATS {
+        let bytes = encode_wav(&signal, &spec(sample_format)).unwrap();
+        assert_eq!(bytes.len() % 2, 0);
+        assert_eq!(
+            u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize,
+            bytes.len() - 8
+        );
+        let (decoded_spec, decoded) = decode_wav(&bytes).unwrap();
+        assert_eq!(decoded_spec, spec(sample_format));
+        assert_eq!(decoded.len(), signal.len());
+        for (x, y) in signal.iter().zip(&decoded) {
+            assert!(
+                (x - y).abs() <= get_step(sample_format),
+                "{sample_format:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn encoded_files_are_read_by_hound() {
+    let signal = signal();
+    for sample_format in [
+        WavSampleFormat::Pcm16,
+        WavSampleFormat::Pcm24,
+        WavSampleFormat::Pcm32,
+    ] {
+        let bytes = encode_wav(&signal, &spec(sample_format)).unwrap();
+        let mut reader = WavReader::new(Cursor::new(bytes)).unwrap();
+        let bits = reader.spec().bits_per_sample;
+        assert_eq!(usize::from(bits) / 8, sample_format.bytes_per_sample());
+        let scale = f64::from(1_u32 << (bits - 1));
+        for (x, y) in signal.iter().zip(reader.samples::<i32>()) {
+            assert_eq!((x * scale).round() as i32, y.unwrap());
+        }
+    }
+    let bytes = encode_wav(&signal, &spec(WavSampleFormat::Float32)).unwrap();
+    let mut reader = WavReader::new(Cursor::new(bytes)).unwrap();
+    for (x, y) in signal.iter().zip(reader.samples::<f32>()) {
+        assert_eq!(*x as f32, y.unwrap());
+    }
+}
+
+#[test]
+fn streamed_file_matches_encoded_file() {
+    let signal = signal();
+    let spec = spec(WavSampleFormat::Pcm24);
+    let mut writer = WavWriter::new(Vec::new(), spec).unwrap();
+    for block in signal.chunks(100) {
+        writer.write_samples(block).unwrap();
+    }
+    let streamed = writer.finalize().unwrap();
+    assert_eq!(streamed, encode_wav(&signal, &spec).unwrap());
+}
+
+#[test]
+fn unfinalized_stream_is_decoded_to_the_end() {
+    let mut writer = WavWriter::new(Vec::new(), spec(WavSampleFormat::Pcm16)).unwrap();
+    writer.write_samples(&[0.5, -0.5]).unwrap();
+    let mut bytes = encode_wav(&[], &spec(WavSampleFormat::Pcm16)).unwrap();
+    bytes.extend_from_slice(&[0x00, 0x40, 0x00, 0xc0]);
+    let (_, samples) = decode_wav(&bytes).unwrap();
+    assert_eq!(samples, vec![0.5, -0.5]);
+}
+
+#[test]
+fn samples_are_clipped() {
+    let bytes = encode_wav(&[2.0, -2.0, f64::NAN], &spec(WavSampleFormat::Pcm16)).unwrap();
+    assert_eq!(&bytes[44..], &[0xff, 0x7f, 0x00, 0x80, 0x00, 0x00]);
+}
+
+#[test]
+fn stereo_and_extra_chunks_are_decoded() {
+    let spec = WavSpec {
+        sample_rate: 8000,
+        channels: 2,
+        sample_format: WavSampleFormat::Pcm16,
+    };
+    let bytes = encode_wav(&[0.25, -0.25, 0.5, -0.5], &spec).unwrap();
+    // insert an odd-sized chunk between the format and the data chunk
+    let mut with_list = bytes[..36].to_vec();
+    with_list.extend_from_slice(b"LIST\x03\x00\x00\x00abc\x00");
+    with_list.extend_from_slice(&bytes[36..]);
+    let (decoded_spec, samples) = decode_wav(&with_list).unwrap();
+    assert_eq!(decoded_spec, spec);
+    assert_eq!(samples.len(), 4);
+    assert!((samples[3] + 0.5).abs() < 1E-4);
+}
+
+#[test]
+fn partial_frames_are_dropped() {
+    let spec = WavSpec {
+        sample_rate: 8000,
+        channels: 2,
+        sample_format: WavSampleFormat::Pcm16,
+    };
+    let mut bytes = encode_wav(&[0.25, -0.25, 0.5, -0.5], &spec).unwrap();
+    // the left sample of a third frame, without the right one
+    bytes.extend_from_slice(&[0x00, 0x40]);
+    bytes[40..44].copy_from_slice(&10_u32.to_le_bytes());
+    let (_, samples) = decode_wav(&bytes).unwrap();
+    assert_eq!(samples.len(), 4);
+    assert!((samples[3] + 0.5).abs() < 1E-4);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn file_sink_is_patched() {
+    let path = std::env::temp_dir().join("klatt_wav_sink_test.wav");
+    let file = std::fs::File::create(&path).unwrap();
+    let mut writer = WavWriter::new(file, spec(WavSampleFormat::Float32)).unwrap();
+    writer.write_samples(&signal()).unwrap();
+    drop(writer.finalize().unwrap());
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        bytes,
+        encode_wav(&signal(), &spec(WavSampleFormat::Float32)).unwrap()
+    );
+}
+
+#[test]
+fn invalid_files_are_rejected() {
+    assert!(decode_wav(b"RIFF\x04\x00\x00\x00WAVE").is_err());
+    assert!(decode_wav(b"not a wav file").is_err());
+    let mut bytes = encode_wav(&[0.0], &spec(WavSampleFormat::Pcm16)).unwrap();
+    // 12 bit PCM
+    bytes[34] = 12;
+    assert!(decode_wav(&bytes).is_err());
+    let spec = WavSpec {
+        channels: 0,
+        ..spec(WavSampleFormat::Pcm16)
+    };
+    assert!(encode_wav(&[0.0], &spec).is_err());
+}